@@ -0,0 +1,29 @@
+//! Example of recording concurrent threads operating on a shared register.
+
+use std::sync::Mutex;
+use std::thread;
+
+use linearize::{Linearizer, Recorder};
+
+fn main() {
+    let register = Mutex::new(None);
+    let recorder = Recorder::new(Linearizer::new(3));
+
+    thread::scope(|s| {
+        for t in 0..3 {
+            let (register, recorder) = (&register, recorder.clone());
+            s.spawn(move || {
+                for i in 0..5 {
+                    let val = t * 10 + i;
+                    recorder.put(val, || *register.lock().unwrap() = Some(val));
+                    let got = recorder.get(|| *register.lock().unwrap());
+                    println!("Node {} put {} then got {:?}", recorder.node(), val, got);
+                }
+                recorder.stop();
+            });
+        }
+    });
+
+    println!();
+    println!("Linearizable -> {}", recorder.finish());
+}
@@ -0,0 +1,65 @@
+//! `proptest` strategies generating valid histories, for property tests of
+//! code built on the checker.
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::{History, Node, OpSpan, Timestamp};
+
+/// Generate sequences of up to `max_ops` ops (Put, Get, Cas and Fail, with
+/// occasional stop and resume pairs) on `num_nodes` nodes, in the order
+/// they finish. Each node's spans follow one another as required by
+/// `Linearizer::feed_span()`, and all timestamps are unique. Values are
+/// drawn from a small range so that ops often interact; the resulting
+/// histories may or may not be linearizable. Shrinks towards fewer ops and
+/// smaller values and gaps.
+pub fn arb_events(num_nodes: usize, max_ops: usize) -> impl Strategy<Value = Vec<(Node, OpSpan)>> {
+    assert!(num_nodes > 0);
+    vec(
+        (0..num_nodes, 0u8..11, 0u64..3, 0u64..4, 0u64..5, 1u64..5),
+        0..=max_ops,
+    )
+    .prop_map(move |raw| {
+        // ticks are scaled into timestamps such that all timestamps are
+        // unique and each op's ticks keep their order
+        let scale = 2 * num_nodes as u64;
+        let ts = |tick: u64, node: Node, ack: bool| -> Timestamp {
+            tick * scale + 2 * node as u64 + ack as u64
+        };
+
+        let mut clocks = vec![0; num_nodes];
+        let mut events = vec![];
+        for (node, kind, a, b, gap, latency) in raw {
+            let mut req = clocks[node] + gap;
+            if kind == 10 {
+                events.push((node, OpSpan::stopped(ts(req, node, false))));
+                events.push((node, OpSpan::resumed(ts(req + 1 + gap, node, false))));
+                req += 2 + gap;
+            }
+            let ack = req + latency;
+            let (req_ts, ack_ts) = (ts(req, node, false), ts(ack, node, true));
+            let span = match kind {
+                0..=3 => OpSpan::put(a, req_ts, ack_ts),
+                8 => OpSpan::cas(a, b, req_ts, ack_ts),
+                9 => OpSpan::fail(req_ts, ack_ts),
+                _ => OpSpan::get(b.checked_sub(1), req_ts, ack_ts),
+            };
+            events.push((node, span));
+            clocks[node] = ack + 1;
+        }
+        events.sort_by_key(|(_, span)| span.ts_ack);
+        events
+    })
+}
+
+/// Generate histories of up to `max_ops` ops on `num_nodes` nodes; see
+/// `arb_events()`. Histories are not closed; see `History::close()`.
+pub fn arb_history(num_nodes: usize, max_ops: usize) -> impl Strategy<Value = History> {
+    arb_events(num_nodes, max_ops).prop_map(|events| {
+        let mut history = History::new();
+        for (node, span) in events {
+            history.push(node, span).unwrap();
+        }
+        history
+    })
+}
@@ -0,0 +1,289 @@
+//! Compact binary format of histories, for capturing long streams of spans
+//! with little overhead.
+//!
+//! A stream starts with the 4-byte magic `LNZ1`, followed by one record per
+//! event, in the order fed. Each record is a tag byte followed by unsigned
+//! LEB128 varints:
+//!
+//! ```text
+//! 0 put:     <node> <val> <ts_req delta> <duration>
+//! 1 get nil: <node> <ts_req delta> <duration>
+//! 2 get:     <node> <val> <ts_req delta> <duration>
+//! 3 cas:     <node> <old> <new> <ts_req delta> <duration>
+//! 4 fail:    <node> <ts_req delta> <duration>
+//! 5 stopped: <node> <ts delta>
+//! 6 resumed: <node> <ts delta>
+//! ```
+//!
+//! Timestamps are delta-encoded per node: `ts_req delta` is the distance
+//! from the node's previous `ts_ack` (or from 0 for its first event), and
+//! `duration` is `ts_ack - ts_req`. Events must follow the same per-node
+//! rules as `History::push()`, so deltas after a node's first event and
+//! durations are positive; both the writer and the reader reject streams
+//! breaking them.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+use crate::history::check_follows;
+use crate::{History, HistoryError, Linearizer, Node, OpInputs, OpResult, OpSpan, Timestamp};
+
+/// Magic bytes at the start of a binary stream.
+const MAGIC: &[u8; 4] = b"LNZ1";
+
+const TAG_PUT: u8 = 0;
+const TAG_GET_NIL: u8 = 1;
+const TAG_GET: u8 = 2;
+const TAG_CAS: u8 = 3;
+const TAG_FAIL: u8 = 4;
+const TAG_STOPPED: u8 = 5;
+const TAG_RESUMED: u8 = 6;
+
+/// Streaming writer of the binary format. Wrap the underlying writer in a
+/// `BufWriter` for high-throughput capture.
+#[derive(Debug)]
+pub struct BinaryWriter<W: Write> {
+    writer: W,
+
+    /// Last event written of each node.
+    tails: HashMap<Node, OpSpan>,
+
+    /// Scratch buffer of the record being encoded.
+    buf: Vec<u8>,
+}
+
+impl<W: Write> BinaryWriter<W> {
+    /// Create a new writer, writing the stream header right away.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        Ok(BinaryWriter {
+            writer,
+            tails: HashMap::new(),
+            buf: Vec::with_capacity(32),
+        })
+    }
+
+    /// Write one event. Events of the same node must be written in order,
+    /// following the rules of `History::push()`.
+    pub fn write_span(&mut self, node: Node, span: &OpSpan) -> io::Result<()> {
+        let tail = self.tails.get(&node);
+        check_follows(node, tail, span)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        let last_ts = tail.map_or(0, |tail| tail.ts_ack);
+
+        self.buf.clear();
+        let tag = match (&span.inputs, &span.result) {
+            (OpInputs::Put { .. }, _) => TAG_PUT,
+            (OpInputs::Get, OpResult::Get { val: None }) => TAG_GET_NIL,
+            (OpInputs::Get, OpResult::Get { val: Some(_) }) => TAG_GET,
+            (OpInputs::Cas { .. }, _) => TAG_CAS,
+            (OpInputs::Get, _) | (OpInputs::Fail, _) => TAG_FAIL,
+            (OpInputs::Stopped, _) => TAG_STOPPED,
+            (OpInputs::Resumed, _) => TAG_RESUMED,
+        };
+        self.buf.push(tag);
+        put_varint(&mut self.buf, node as u64);
+        match (&span.inputs, &span.result) {
+            (OpInputs::Put { val }, _) | (OpInputs::Get, OpResult::Get { val: Some(val) }) => {
+                put_varint(&mut self.buf, *val);
+            }
+            (OpInputs::Cas { old, new }, _) => {
+                put_varint(&mut self.buf, *old);
+                put_varint(&mut self.buf, *new);
+            }
+            _ => {}
+        }
+        put_varint(&mut self.buf, span.ts_req - last_ts);
+        if span.is_normal() {
+            put_varint(&mut self.buf, span.ts_ack - span.ts_req);
+        }
+
+        self.writer.write_all(&self.buf)?;
+        self.tails.insert(node, span.clone());
+        Ok(())
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Unwrap the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Streaming reader of the binary format, yielding events one at a time
+/// without buffering the whole history. Wrap the underlying reader in a
+/// `BufReader` for high throughput.
+#[derive(Debug)]
+pub struct BinaryReader<R: Read> {
+    reader: R,
+
+    /// Last event read of each node.
+    tails: HashMap<Node, OpSpan>,
+
+    /// Number of records read so far.
+    records: usize,
+}
+
+impl<R: Read> BinaryReader<R> {
+    /// Create a new reader, checking the stream header right away.
+    pub fn new(mut reader: R) -> Result<Self, HistoryError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(HistoryError::Invalid("not a binary history stream".into()));
+        }
+        Ok(BinaryReader {
+            reader,
+            tails: HashMap::new(),
+            records: 0,
+        })
+    }
+
+    /// Read the next event, or `None` at the end of stream.
+    pub fn read_span(&mut self) -> Result<Option<(Node, OpSpan)>, HistoryError> {
+        let mut tag = [0];
+        if self.reader.read(&mut tag)? == 0 {
+            return Ok(None);
+        }
+        self.records += 1;
+        let record = self.records;
+        let truncated = |e: io::Error| {
+            if e.kind() == io::ErrorKind::UnexpectedEof {
+                HistoryError::Invalid(format!("record {} truncated", record))
+            } else {
+                HistoryError::Io(e)
+            }
+        };
+
+        let node = self.varint().map_err(truncated)? as Node;
+        let vals = match tag[0] {
+            TAG_PUT | TAG_GET => (Some(self.varint().map_err(truncated)?), None),
+            TAG_CAS => (
+                Some(self.varint().map_err(truncated)?),
+                Some(self.varint().map_err(truncated)?),
+            ),
+            TAG_GET_NIL | TAG_FAIL | TAG_STOPPED | TAG_RESUMED => (None, None),
+            tag => {
+                return Err(HistoryError::Invalid(format!(
+                    "record {} has unknown tag {}",
+                    record, tag
+                )))
+            }
+        };
+        let last_ts: Timestamp = self.tails.get(&node).map_or(0, |tail| tail.ts_ack);
+        let ts_req = last_ts
+            .checked_add(self.varint().map_err(truncated)?)
+            .ok_or_else(|| HistoryError::Invalid(format!("record {} overflows", record)))?;
+        let ts_ack = match tag[0] {
+            TAG_STOPPED | TAG_RESUMED => ts_req,
+            _ => ts_req
+                .checked_add(self.varint().map_err(truncated)?)
+                .filter(|&ts_ack| ts_ack > ts_req)
+                .ok_or_else(|| {
+                    HistoryError::Invalid(format!("record {} has invalid duration", record))
+                })?,
+        };
+
+        let span = match (tag[0], vals) {
+            (TAG_PUT, (Some(val), _)) => OpSpan::put(val, ts_req, ts_ack),
+            (TAG_GET_NIL, _) => OpSpan::get(None, ts_req, ts_ack),
+            (TAG_GET, (val, _)) => OpSpan::get(val, ts_req, ts_ack),
+            (TAG_CAS, (Some(old), Some(new))) => OpSpan::cas(old, new, ts_req, ts_ack),
+            (TAG_FAIL, _) => OpSpan::fail(ts_req, ts_ack),
+            (TAG_STOPPED, _) => OpSpan::stopped(ts_req),
+            _ => OpSpan::resumed(ts_req),
+        };
+        check_follows(node, self.tails.get(&node), &span).map_err(|e| match e {
+            HistoryError::Invalid(msg) => {
+                HistoryError::Invalid(format!("record {}: {}", record, msg))
+            }
+            e => e,
+        })?;
+        self.tails.insert(node, span.clone());
+        Ok(Some((node, span)))
+    }
+
+    /// Feed all remaining events into the given linearizer as they are
+    /// read, stopping at the first violation. Returns the index (among
+    /// events fed by this call) of the event that revealed the violation,
+    /// if any.
+    pub fn feed_into(
+        &mut self,
+        linearizer: &mut Linearizer,
+    ) -> Result<Option<usize>, HistoryError> {
+        let mut index = 0;
+        while let Some((node, span)) = self.read_span()? {
+            if node >= linearizer.num_nodes {
+                return Err(HistoryError::Invalid(format!(
+                    "record {}: node {} out of range for {} nodes",
+                    self.records, node, linearizer.num_nodes
+                )));
+            }
+            if !linearizer.feed_span(node, span) {
+                return Ok(Some(index));
+            }
+            index += 1;
+        }
+        Ok(None)
+    }
+
+    /// Read one unsigned LEB128 varint.
+    fn varint(&mut self) -> io::Result<u64> {
+        let mut val = 0;
+        for shift in (0..64).step_by(7) {
+            let mut byte = [0];
+            self.reader.read_exact(&mut byte)?;
+            val |= ((byte[0] & 0x7f) as u64) << shift;
+            if byte[0] & 0x80 == 0 {
+                return Ok(val);
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "varint too long",
+        ))
+    }
+}
+
+impl<R: Read> Iterator for BinaryReader<R> {
+    type Item = Result<(Node, OpSpan), HistoryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_span().transpose()
+    }
+}
+
+/// Append one unsigned LEB128 varint.
+fn put_varint(buf: &mut Vec<u8>, mut val: u64) {
+    while val >= 0x80 {
+        buf.push((val as u8) | 0x80);
+        val >>= 7;
+    }
+    buf.push(val as u8);
+}
+
+impl History {
+    /// Read a history in the binary format, checking per-node ordering.
+    pub fn read_binary(reader: impl Read) -> Result<Self, HistoryError> {
+        let mut history = History::new();
+        for event in BinaryReader::new(reader)? {
+            let (node, span) = event?;
+            history.push(node, span)?;
+        }
+        Ok(history)
+    }
+
+    /// Write the history in the binary format.
+    pub fn write_binary(&self, writer: impl Write) -> Result<(), HistoryError> {
+        let mut writer = BinaryWriter::new(writer)?;
+        for (node, span) in self.events() {
+            writer.write_span(*node, span)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
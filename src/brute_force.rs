@@ -0,0 +1,86 @@
+//! Offline brute-force linearizability checker, exponential but simple
+//! enough to serve as a reference for cross-checking the on-line one in
+//! property tests; enabled by the `proptest` feature.
+
+use std::collections::HashSet;
+
+use crate::{Clock, History, OpInputs, OpResult, OpSpan, Value};
+
+/// Search state: which ops are linearized, and the current value (`None`
+/// if uncertain, `Some(None)` if nil).
+type State = (Vec<bool>, Option<Option<Value>>);
+
+/// Apply `op` on top of `current`, returning the new current value, or
+/// `None` if the op cannot take effect there.
+fn apply<T: Clock>(
+    current: Option<Option<Value>>,
+    op: &OpSpan<T>,
+) -> Option<Option<Option<Value>>> {
+    match (&op.inputs, &op.result) {
+        (OpInputs::Put { val }, OpResult::Put) => Some(Some(Some(*val))),
+        (OpInputs::Get, OpResult::Get { val }) => {
+            (current.is_none() || current == Some(*val)).then_some(current)
+        }
+        (OpInputs::Cas { old, new }, OpResult::Put) => {
+            (current.is_none() || current == Some(Some(*old))).then_some(Some(Some(*new)))
+        }
+        (OpInputs::Fail, _) => Some(None),
+        _ => None,
+    }
+}
+
+/// Depth-first search for a linearization of the remaining ops, memoizing
+/// the states already explored.
+fn search<T: Clock>(ops: &[&OpSpan<T>], state: State, visited: &mut HashSet<State>) -> bool {
+    let (done, current) = &state;
+    if done.iter().all(|d| *d) {
+        return true;
+    }
+    if visited.contains(&state) {
+        return false;
+    }
+    for (i, op) in ops.iter().enumerate() {
+        // an op can go next only if no remaining op finished before it
+        if done[i]
+            || ops
+                .iter()
+                .enumerate()
+                .any(|(j, other)| !done[j] && other.ts_ack.precedes(&op.ts_req, 0))
+        {
+            continue;
+        }
+        if let Some(next) = apply(*current, op) {
+            let mut next_done = done.clone();
+            next_done[i] = true;
+            if search(ops, (next_done, next), visited) {
+                return true;
+            }
+        }
+    }
+    visited.insert(state);
+    false
+}
+
+impl<T: Clock> History<T> {
+    /// Check offline whether all normal ops of the history are linearizable
+    /// against a register initially nil, trying all orders that respect
+    /// real time. A Fail leaves the value uncertain until the next write,
+    /// as in `Linearizer`. Only checks the `Linearizable` model, with no
+    /// clock skew allowed, so it cannot cross-check the other
+    /// `ConsistencyModel`s or `with_max_clock_skew()`. Takes exponential
+    /// time in the worst case, so is only meant as a reference on small
+    /// histories; enabled by the `proptest` feature.
+    pub fn is_linearizable_brute_force(&self) -> bool {
+        let ops: Vec<&OpSpan<T>> = self
+            .events()
+            .iter()
+            .map(|(_, span)| span)
+            .filter(|span| span.is_normal())
+            .collect();
+        search(
+            &ops,
+            (vec![false; ops.len()], Some(None)),
+            &mut HashSet::new(),
+        )
+    }
+}
@@ -0,0 +1,113 @@
+//! Timestamp types that can be compared for happens-before order, possibly
+//! only partially.
+
+use std::fmt;
+use std::hash;
+
+use crate::Timestamp;
+
+/// Trait of timestamps stamped on operation spans. Timestamps only need to
+/// be partially ordered by the happens-before relation; two timestamps that
+/// are not ordered either way are considered concurrent.
+pub trait Clock: Clone + Eq + hash::Hash + fmt::Debug + fmt::Display {
+    /// Check if `self` happened strictly before `other`.
+    fn happens_before(&self, other: &Self) -> bool;
+
+    /// Make a copy of `self` shifted later by `slack` units of physical
+    /// time, used to account for clock skew and staleness bounds. Timestamps
+    /// without a physical time component are returned unchanged.
+    fn shifted(&self, slack: u64) -> Self;
+
+    /// Check if `self`, shifted later by `slack`, happened before or at
+    /// `other`.
+    fn precedes(&self, other: &Self, slack: u64) -> bool {
+        if slack == 0 {
+            self == other || self.happens_before(other)
+        } else {
+            let shifted = self.shifted(slack);
+            shifted == *other || shifted.happens_before(other)
+        }
+    }
+}
+
+/// Physical (wall-clock) timestamps, expected to be globally comparable.
+impl Clock for Timestamp {
+    fn happens_before(&self, other: &Self) -> bool {
+        self < other
+    }
+
+    fn shifted(&self, slack: u64) -> Self {
+        self.saturating_add(slack)
+    }
+
+    fn precedes(&self, other: &Self, slack: u64) -> bool {
+        self.saturating_add(slack) <= *other
+    }
+}
+
+/// Hybrid logical clock timestamp, ordered lexicographically by its
+/// physical then logical component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HybridTimestamp {
+    pub physical: u64,
+    pub logical: u64,
+}
+
+impl HybridTimestamp {
+    /// Create a new hybrid logical clock timestamp.
+    pub fn new(physical: u64, logical: u64) -> Self {
+        HybridTimestamp { physical, logical }
+    }
+}
+
+impl Clock for HybridTimestamp {
+    fn happens_before(&self, other: &Self) -> bool {
+        (self.physical, self.logical) < (other.physical, other.logical)
+    }
+
+    fn shifted(&self, slack: u64) -> Self {
+        HybridTimestamp {
+            physical: self.physical.saturating_add(slack),
+            logical: self.logical,
+        }
+    }
+}
+
+impl fmt::Display for HybridTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.physical, self.logical)
+    }
+}
+
+/// Vector clock timestamp, with one counter per node. Two vector clocks of
+/// different lengths are never ordered.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VectorTimestamp(pub Vec<u64>);
+
+impl Clock for VectorTimestamp {
+    fn happens_before(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self.0.iter().zip(other.0.iter()).all(|(s, o)| s <= o)
+            && self != other
+    }
+
+    fn shifted(&self, _slack: u64) -> Self {
+        // no notion of physical time
+        self.clone()
+    }
+}
+
+impl fmt::Display for VectorTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, c) in self.0.iter().enumerate() {
+            write!(f, "{}", c)?;
+            if i < self.0.len() - 1 {
+                write!(f, ",")?;
+            }
+        }
+        write!(f, "]")
+    }
+}
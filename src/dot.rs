@@ -0,0 +1,190 @@
+//! Graphviz export of the possibility state graph explored by a linearizer,
+//! for understanding why the frontier explodes on some histories.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io::{self, Write};
+
+use crate::{CheckerObserver, Clock, Node, OpSpan, PruneReason, StateView};
+
+/// States and transitions explored during one `feed_span()` call.
+#[derive(Debug, Clone)]
+struct FeedGraph {
+    /// Index of the feed, counting from 0.
+    index: usize,
+
+    /// Node and span fed, for labeling.
+    label: String,
+
+    /// State key -> (state label, whether it is a dead end).
+    states: BTreeMap<u64, (String, bool)>,
+
+    /// (parent key, child key, op label) transitions.
+    edges: BTreeSet<(u64, u64, String)>,
+
+    /// (parent key, op label, reason) failed transitions.
+    pruned: Vec<(u64, String, PruneReason)>,
+}
+
+/// Observer recording the possibility graph explored by a linearizer, as
+/// states labeled by their current value and per-node queue lengths and
+/// edges labeled by the op applied, to be written as a Graphviz DOT file.
+/// Each feed is drawn as its own cluster, since appending the fed span
+/// changes every state.
+///
+/// ```
+/// use linearize::{DotObserver, Linearizer, OpSpan};
+///
+/// let mut observer = DotObserver::new().with_last_feeds(16);
+/// let mut linearizer = Linearizer::new(1);
+/// linearizer.feed_span_with(0, OpSpan::put(1, 1, 2), &mut observer);
+/// linearizer.feed_span_with(0, OpSpan::stopped(3), &mut observer);
+///
+/// let mut dot = vec![];
+/// observer.write_dot(&mut dot).unwrap();
+/// assert!(String::from_utf8(dot).unwrap().starts_with("digraph"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DotObserver {
+    /// Number of most recent feeds to keep, or `None` to keep all.
+    last_feeds: Option<usize>,
+
+    /// Graphs of the feeds kept.
+    feeds: VecDeque<FeedGraph>,
+
+    /// Total number of feeds seen.
+    num_feeds: usize,
+}
+
+/// Label a state by its current value and per-node queue lengths.
+fn state_label<T: Clock>(state: &StateView<T>) -> String {
+    let val = match state.current_value() {
+        None => "?".into(),
+        Some(None) => "nil".into(),
+        Some(Some(val)) => val.to_string(),
+    };
+    let queues: Vec<String> = (0..state.num_nodes())
+        .map(|node| state.queue_len(node).to_string())
+        .collect();
+    format!("{} [{}]", val, queues.join(","))
+}
+
+/// Escape text for a quoted DOT string.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl DotObserver {
+    /// Create an observer keeping the graphs of all feeds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep only the graphs of the most recent `n` feeds.
+    pub fn with_last_feeds(mut self, n: usize) -> Self {
+        self.last_feeds = Some(n);
+        self
+    }
+
+    /// Get the graph of the current feed, recording given state in it.
+    fn record<T: Clock>(&mut self, state: &StateView<T>) -> Option<&mut FeedGraph> {
+        let feed = self.feeds.back_mut()?;
+        feed.states
+            .entry(state.key())
+            .or_insert_with(|| (state_label(state), false));
+        Some(feed)
+    }
+
+    /// Write the recorded graph in the Graphviz DOT format.
+    pub fn write_dot(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "digraph possibilities {{")?;
+        writeln!(writer, "  node [shape=box, fontname=\"monospace\"];")?;
+        for feed in &self.feeds {
+            let id = |key: u64| format!("f{}_{:x}", feed.index, key);
+            writeln!(writer, "  subgraph cluster_{} {{", feed.index)?;
+            writeln!(
+                writer,
+                "    label=\"feed {}: {}\";",
+                feed.index,
+                escape(&feed.label)
+            )?;
+            for (key, (label, dead_end)) in &feed.states {
+                let color = if *dead_end { ", color=red" } else { "" };
+                writeln!(
+                    writer,
+                    "    {} [label=\"{}\"{}];",
+                    id(*key),
+                    escape(label),
+                    color
+                )?;
+            }
+            for (parent, child, op) in &feed.edges {
+                writeln!(
+                    writer,
+                    "    {} -> {} [label=\"{}\"];",
+                    id(*parent),
+                    id(*child),
+                    escape(op)
+                )?;
+            }
+            for (i, (parent, op, reason)) in feed.pruned.iter().enumerate() {
+                let pruned = format!("f{}_pruned{}", feed.index, i);
+                writeln!(writer, "    {} [shape=point, color=gray];", pruned)?;
+                writeln!(
+                    writer,
+                    "    {} -> {} [label=\"{} ({})\", style=dashed, color=gray];",
+                    id(*parent),
+                    pruned,
+                    escape(op),
+                    reason
+                )?;
+            }
+            writeln!(writer, "  }}")?;
+        }
+        writeln!(writer, "}}")
+    }
+}
+
+impl<T: Clock> CheckerObserver<T> for DotObserver {
+    fn on_feed(&mut self, node: Node, span: &OpSpan<T>) {
+        if self.last_feeds == Some(self.feeds.len()) {
+            self.feeds.pop_front();
+        }
+        if self.last_feeds != Some(0) {
+            self.feeds.push_back(FeedGraph {
+                index: self.num_feeds,
+                label: format!("{} {}", node, span),
+                states: BTreeMap::new(),
+                edges: BTreeSet::new(),
+                pruned: vec![],
+            });
+        }
+        self.num_feeds += 1;
+    }
+
+    fn on_created(
+        &mut self,
+        parent: StateView<T>,
+        node: Node,
+        op: &OpSpan<T>,
+        child: StateView<T>,
+    ) {
+        self.record(&child);
+        if let Some(feed) = self.record(&parent) {
+            feed.edges
+                .insert((parent.key(), child.key(), format!("{} {}", node, op)));
+        }
+    }
+
+    fn on_pruned(&mut self, parent: StateView<T>, node: Node, op: &OpSpan<T>, reason: PruneReason) {
+        if let Some(feed) = self.record(&parent) {
+            feed.pruned
+                .push((parent.key(), format!("{} {}", node, op), reason));
+        }
+    }
+
+    fn on_dead_end(&mut self, state: StateView<T>) {
+        if let Some(feed) = self.record(&state) {
+            feed.states.get_mut(&state.key()).unwrap().1 = true;
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Random history generator that simulates nodes performing ops against a
+//! correct in-memory register, with optional fault injection, for producing
+//! test inputs in bulk.
+
+use crate::{History, Node, OpSpan, Timestamp, Value};
+
+/// Faults injected into the simulated register, as probabilities in
+/// `[0, 1]`; all zero (no faults) by default.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Faults {
+    /// Chance of a Get returning the value before the latest write instead
+    /// of the current one.
+    pub stale_read: f64,
+
+    /// Chance of an acked Put not taking effect at all.
+    pub lost_write: f64,
+}
+
+/// Small seeded pseudo-random number generator (SplitMix64), so that
+/// generated histories are reproducible without extra dependencies.
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform number in `[0, n)`; `n` must be positive.
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// True with probability `p`.
+    fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+}
+
+/// Kind of a simulated op.
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Put(Value),
+    Get,
+    Fail(Value),
+}
+
+/// An op planned on a node, with times in simulation ticks.
+#[derive(Debug, Clone)]
+struct Planned {
+    node: Node,
+    kind: Kind,
+    req: u64,
+    ack: u64,
+    lin: u64, // tick at which it takes effect, in [req, ack)
+}
+
+/// Generator of random histories: simulates nodes performing random
+/// Put/Get/Fail ops, with occasional stops and resumes, against a correct
+/// register with random latencies. Without faults, the histories produced
+/// are always linearizable; with `Faults` injected, they most likely are
+/// not. Histories end with every one of the `num_nodes` nodes stopped, even
+/// ones that performed no op, so they can be checked in full by a
+/// `Linearizer` of `num_nodes` nodes as is.
+///
+/// ```
+/// use linearize::{HistoryGenerator, Linearizer};
+///
+/// let mut generator = HistoryGenerator::new(3, 42).with_num_ops(50);
+/// let history = generator.generate();
+/// let mut linearizer = Linearizer::new(3);
+/// assert_eq!(history.feed_into(&mut linearizer), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HistoryGenerator {
+    /// Number of nodes.
+    num_nodes: usize,
+
+    /// Number of ops per history, excluding stops and resumes.
+    num_ops: usize,
+
+    /// Maximum latency of an op, and gap between a node's ops, in ticks.
+    max_latency: u64,
+
+    /// Chance of an op failing, leaving its effect unknown.
+    fail_rate: f64,
+
+    /// Chance of a node stopping for a while before an op.
+    stop_rate: f64,
+
+    /// Faults to inject.
+    faults: Faults,
+
+    /// Random number generator, advanced across histories.
+    rng: Rng,
+}
+
+impl HistoryGenerator {
+    /// Create a generator for `num_nodes` nodes seeded with `seed`.
+    pub fn new(num_nodes: usize, seed: u64) -> Self {
+        assert!(num_nodes > 0);
+        HistoryGenerator {
+            num_nodes,
+            num_ops: 100,
+            max_latency: 10,
+            fail_rate: 0.05,
+            stop_rate: 0.02,
+            faults: Faults::default(),
+            rng: Rng(seed),
+        }
+    }
+
+    /// Set the number of ops per history (100 by default).
+    pub fn with_num_ops(mut self, num_ops: usize) -> Self {
+        self.num_ops = num_ops;
+        self
+    }
+
+    /// Set the maximum latency of an op in ticks (10 by default).
+    pub fn with_max_latency(mut self, max_latency: u64) -> Self {
+        assert!(max_latency > 0);
+        self.max_latency = max_latency;
+        self
+    }
+
+    /// Set the chance of an op failing (0.05 by default).
+    pub fn with_fail_rate(mut self, fail_rate: f64) -> Self {
+        self.fail_rate = fail_rate;
+        self
+    }
+
+    /// Set the chance of a node stopping before an op (0.02 by default).
+    pub fn with_stop_rate(mut self, stop_rate: f64) -> Self {
+        self.stop_rate = stop_rate;
+        self
+    }
+
+    /// Set the faults to inject (none by default).
+    pub fn with_faults(mut self, faults: Faults) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Generate a new random history.
+    pub fn generate(&mut self) -> History {
+        // ticks are scaled into timestamps such that all timestamps are
+        // unique and each op's ticks keep their order
+        let scale = 2 * self.num_nodes as u64;
+        let ts = |tick: u64, node: Node, ack: bool| -> Timestamp {
+            tick * scale + 2 * node as u64 + ack as u64
+        };
+
+        // plan the ops, always advancing the node furthest behind
+        let mut clocks = vec![0; self.num_nodes];
+        let mut planned = vec![];
+        let mut markers = vec![];
+        let mut next_val = 1;
+        for _ in 0..self.num_ops {
+            let node = (0..self.num_nodes).min_by_key(|&n| clocks[n]).unwrap();
+            if self.rng.chance(self.stop_rate) {
+                let stopped = clocks[node] + self.rng.below(self.max_latency);
+                let resumed = stopped + 1 + self.rng.below(4 * self.max_latency);
+                markers.push((node, OpSpan::stopped(ts(stopped, node, false))));
+                markers.push((node, OpSpan::resumed(ts(resumed, node, false))));
+                clocks[node] = resumed + 1;
+            }
+            let kind = if self.rng.chance(self.fail_rate) {
+                next_val += 1;
+                Kind::Fail(next_val - 1)
+            } else if self.rng.chance(0.5) {
+                next_val += 1;
+                Kind::Put(next_val - 1)
+            } else {
+                Kind::Get
+            };
+            let req = clocks[node] + self.rng.below(self.max_latency);
+            let ack = req + 1 + self.rng.below(self.max_latency);
+            let lin = req + self.rng.below(ack - req);
+            planned.push(Planned {
+                node,
+                kind,
+                req,
+                ack,
+                lin,
+            });
+            clocks[node] = ack + 1;
+        }
+
+        // apply the ops to the register in the order they take effect
+        let mut order: Vec<usize> = (0..planned.len()).collect();
+        order.sort_by_key(|&i| (planned[i].lin, planned[i].node));
+        let mut results = vec![None; planned.len()];
+        let (mut current, mut previous): (Option<Value>, Option<Option<Value>>) = (None, None);
+        for i in order {
+            match planned[i].kind {
+                Kind::Put(val) if !self.rng.chance(self.faults.lost_write) => {
+                    previous = Some(current);
+                    current = Some(val);
+                }
+                Kind::Fail(val) if self.rng.chance(0.5) => {
+                    previous = Some(current);
+                    current = Some(val);
+                }
+                Kind::Get => {
+                    results[i] = match previous {
+                        Some(stale)
+                            if stale != current && self.rng.chance(self.faults.stale_read) =>
+                        {
+                            stale
+                        }
+                        _ => current,
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // record the events in the order they finish
+        let mut events = markers;
+        for (op, result) in planned.iter().zip(results) {
+            let (req, ack) = (ts(op.req, op.node, false), ts(op.ack, op.node, true));
+            let span = match op.kind {
+                Kind::Put(val) => OpSpan::put(val, req, ack),
+                Kind::Get => OpSpan::get(result, req, ack),
+                Kind::Fail(_) => OpSpan::fail(req, ack),
+            };
+            events.push((op.node, span));
+        }
+        // stop every node after all ops, so that all configured nodes are
+        // present and the history can be checked in full
+        let end = clocks.iter().max().copied().unwrap_or(0);
+        for node in 0..self.num_nodes {
+            events.push((node, OpSpan::stopped(ts(end, node, false))));
+        }
+        events.sort_by_key(|(_, span)| span.ts_ack);
+        let mut history = History::new();
+        for (node, span) in events {
+            history.push(node, span).unwrap();
+        }
+        history
+    }
+}
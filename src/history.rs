@@ -0,0 +1,307 @@
+//! Recorded histories of operation spans, and a simple line-based text
+//! format for them.
+//!
+//! Each non-empty line of the text format is one event, in the order fed;
+//! `#` starts a comment:
+//!
+//! ```text
+//! # <node> put <val> <ts_req> <ts_ack>
+//! 0 put 8 100 105
+//! # <node> get <val|nil> <ts_req> <ts_ack>
+//! 1 get nil 101 103
+//! # <node> cas <old> <new> <ts_req> <ts_ack>
+//! 0 cas 8 9 106 108
+//! # <node> fail <ts_req> <ts_ack>
+//! 1 fail 104 107
+//! # <node> stopped|resumed <ts>
+//! 0 stopped 109
+//! ```
+
+use std::error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use crate::{Clock, Linearizer, Node, OpInputs, OpResult, OpSpan, Timestamp, Value};
+
+/// Largest number of nodes a history may have, bounding the memory taken
+/// by per-node state when node IDs come from untrusted input.
+const MAX_NODES: usize = 1 << 16;
+
+/// History error type.
+#[derive(Debug)]
+pub enum HistoryError {
+    /// I/O error when reading or writing a history.
+    Io(io::Error),
+
+    /// Malformed event at given (1-based) line.
+    Parse { line: usize, msg: String },
+
+    /// Invalid event, such as one violating the per-node ordering rules.
+    Invalid(String),
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryError::Io(e) => write!(f, "I/O error: {}", e),
+            HistoryError::Parse { line, msg } => write!(f, "line {}: {}", line, msg),
+            HistoryError::Invalid(msg) => write!(f, "invalid event: {}", msg),
+        }
+    }
+}
+
+impl error::Error for HistoryError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            HistoryError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HistoryError {
+    fn from(e: io::Error) -> Self {
+        HistoryError::Io(e)
+    }
+}
+
+/// A recorded history: a sequence of `(Node, OpSpan)` events in the order
+/// they should be fed, respecting per-node ordering rules.
+#[derive(Debug, Clone)]
+pub struct History<T: Clock = Timestamp> {
+    /// Number of nodes, i.e., largest node ID seen plus one.
+    num_nodes: usize,
+
+    /// Events in feeding order.
+    events: Vec<(Node, OpSpan<T>)>,
+
+    /// Node-indexed last event index of that node.
+    last_events: Vec<Option<usize>>,
+}
+
+impl<T: Clock> Default for History<T> {
+    fn default() -> Self {
+        History {
+            num_nodes: 0,
+            events: vec![],
+            last_events: vec![],
+        }
+    }
+}
+
+impl<T: Clock> History<T> {
+    /// Create a new empty history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number of nodes.
+    pub fn num_nodes(&self) -> usize {
+        self.num_nodes
+    }
+
+    /// Get the events in feeding order.
+    pub fn events(&self) -> &[(Node, OpSpan<T>)] {
+        &self.events
+    }
+
+    /// Append an event, checking that its node ID is below 65536 and that
+    /// it follows the previous event of the same node as required by the
+    /// linearizer.
+    pub fn push(&mut self, node: Node, span: OpSpan<T>) -> Result<(), HistoryError> {
+        if node >= MAX_NODES {
+            return Err(HistoryError::Invalid(format!(
+                "node {} out of range for at most {} nodes",
+                node, MAX_NODES
+            )));
+        }
+        let tail = self
+            .last_events
+            .get(node)
+            .copied()
+            .flatten()
+            .map(|last| &self.events[last].1);
+        check_follows(node, tail, &span)?;
+        if node >= self.num_nodes {
+            self.num_nodes = node + 1;
+            self.last_events.resize(self.num_nodes, None);
+        }
+
+        self.last_events[node] = Some(self.events.len());
+        self.events.push((node, span));
+        Ok(())
+    }
+
+    /// Feed all events into the given linearizer, stopping at the first
+    /// violation. Returns the index of the event that revealed the
+    /// violation, if any.
+    pub fn feed_into(&self, linearizer: &mut Linearizer<T>) -> Option<usize> {
+        self.events
+            .iter()
+            .position(|(node, span)| !linearizer.feed_span(*node, span.clone()))
+    }
+}
+
+impl History {
+    /// Append a stop event for every node not stopped yet, timestamped after
+    /// all existing events, so that all ops get checked.
+    pub fn close(&mut self) {
+        let mut ts = self
+            .events
+            .iter()
+            .map(|(_, span)| span.ts_ack)
+            .max()
+            .unwrap_or(0);
+        for node in 0..self.num_nodes {
+            let stopped = self.last_events[node]
+                .is_some_and(|i| matches!(self.events[i].1.inputs, OpInputs::Stopped));
+            if !stopped {
+                ts += 1;
+                self.push(node, OpSpan::stopped(ts)).unwrap();
+            }
+        }
+    }
+
+    /// Read a history in the text format.
+    pub fn read_text(reader: impl BufRead) -> Result<Self, HistoryError> {
+        let mut history = History::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (node, span) =
+                parse_event(line).map_err(|msg| HistoryError::Parse { line: i + 1, msg })?;
+            history.push(node, span).map_err(|e| HistoryError::Parse {
+                line: i + 1,
+                msg: e.to_string(),
+            })?;
+        }
+        Ok(history)
+    }
+
+    /// Write the history in the text format.
+    pub fn write_text(&self, mut writer: impl Write) -> Result<(), HistoryError> {
+        for (node, span) in &self.events {
+            writeln!(writer, "{}", format_event(*node, span))?;
+        }
+        Ok(())
+    }
+}
+
+/// Check that `span` can follow `tail`, the previous event of the same node
+/// if any, as required by the linearizer.
+pub(crate) fn check_follows<T: Clock>(
+    node: Node,
+    tail: Option<&OpSpan<T>>,
+    span: &OpSpan<T>,
+) -> Result<(), HistoryError> {
+    if span.is_normal() && !span.ts_req.happens_before(&span.ts_ack) {
+        return Err(HistoryError::Invalid(format!(
+            "{:?} does not start before it finishes",
+            span
+        )));
+    }
+    if let Some(tail) = tail {
+        if !tail.ts_ack.happens_before(&span.ts_req) {
+            return Err(HistoryError::Invalid(format!(
+                "{:?} does not start after {:?} on node {}",
+                span, tail, node
+            )));
+        }
+        if matches!(tail.inputs, OpInputs::Stopped) != matches!(span.inputs, OpInputs::Resumed) {
+            return Err(HistoryError::Invalid(format!(
+                "{:?} cannot follow {:?} on node {}",
+                span, tail, node
+            )));
+        }
+    } else if matches!(span.inputs, OpInputs::Resumed) {
+        return Err(HistoryError::Invalid(format!(
+            "{:?} is the first event on node {}",
+            span, node
+        )));
+    }
+    Ok(())
+}
+
+/// Format one event as a line of the text format.
+pub(crate) fn format_event(node: Node, span: &OpSpan) -> String {
+    match span.inputs {
+        OpInputs::Put { val } => {
+            format!("{} put {} {} {}", node, val, span.ts_req, span.ts_ack)
+        }
+        OpInputs::Get => {
+            let val = match span.result {
+                OpResult::Get { val: Some(val) } => val.to_string(),
+                _ => "nil".into(),
+            };
+            format!("{} get {} {} {}", node, val, span.ts_req, span.ts_ack)
+        }
+        OpInputs::Cas { old, new } => format!(
+            "{} cas {} {} {} {}",
+            node, old, new, span.ts_req, span.ts_ack
+        ),
+        OpInputs::Fail => format!("{} fail {} {}", node, span.ts_req, span.ts_ack),
+        OpInputs::Stopped => format!("{} stopped {}", node, span.ts_req),
+        OpInputs::Resumed => format!("{} resumed {}", node, span.ts_req),
+    }
+}
+
+/// Parse one line of the text format into an event.
+fn parse_event(line: &str) -> Result<(Node, OpSpan), String> {
+    fn num<N: std::str::FromStr>(token: Option<&str>, what: &str) -> Result<N, String> {
+        let token = token.ok_or(format!("missing {}", what))?;
+        token
+            .parse()
+            .map_err(|_| format!("invalid {} '{}'", what, token))
+    }
+
+    let mut tokens = line.split_whitespace();
+    let node: Node = num(tokens.next(), "node")?;
+    let kind = tokens.next().ok_or("missing op type")?;
+    let span = match kind.to_ascii_lowercase().as_str() {
+        "put" => {
+            let val: Value = num(tokens.next(), "value")?;
+            let (ts_req, ts_ack) = (num(tokens.next(), "ts_req")?, num(tokens.next(), "ts_ack")?);
+            if ts_ack <= ts_req {
+                return Err("ts_ack must be larger than ts_req".into());
+            }
+            OpSpan::put(val, ts_req, ts_ack)
+        }
+        "get" => {
+            let val: Option<Value> = match tokens.next() {
+                Some("nil") => None,
+                token => Some(num(token, "value")?),
+            };
+            let (ts_req, ts_ack) = (num(tokens.next(), "ts_req")?, num(tokens.next(), "ts_ack")?);
+            if ts_ack <= ts_req {
+                return Err("ts_ack must be larger than ts_req".into());
+            }
+            OpSpan::get(val, ts_req, ts_ack)
+        }
+        "cas" => {
+            let (old, new): (Value, Value) =
+                (num(tokens.next(), "old")?, num(tokens.next(), "new")?);
+            let (ts_req, ts_ack) = (num(tokens.next(), "ts_req")?, num(tokens.next(), "ts_ack")?);
+            if ts_ack <= ts_req {
+                return Err("ts_ack must be larger than ts_req".into());
+            }
+            OpSpan::cas(old, new, ts_req, ts_ack)
+        }
+        "fail" => {
+            let (ts_req, ts_ack) = (num(tokens.next(), "ts_req")?, num(tokens.next(), "ts_ack")?);
+            if ts_ack <= ts_req {
+                return Err("ts_ack must be larger than ts_req".into());
+            }
+            OpSpan::fail(ts_req, ts_ack)
+        }
+        "stopped" => OpSpan::stopped(num(tokens.next(), "ts")?),
+        "resumed" => OpSpan::resumed(num(tokens.next(), "ts")?),
+        _ => return Err(format!("unknown op type '{}'", kind)),
+    };
+    if let Some(token) = tokens.next() {
+        return Err(format!("unexpected trailing '{}'", token));
+    }
+    Ok((node, span))
+}
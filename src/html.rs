@@ -0,0 +1,192 @@
+//! Self-contained HTML report visualizing a history, with the ops involved
+//! in a violation highlighted; suitable for attaching to bug reports.
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+use crate::{History, Node, OpInputs, OpSpan, Timestamp, Violation};
+
+/// Width of the time axis in the drawn SVG, in pixels.
+const AXIS_WIDTH: u64 = 960;
+
+/// Height of each node's lane, in pixels.
+const LANE_HEIGHT: u64 = 36;
+
+/// Left margin holding node labels, in pixels.
+const MARGIN: u64 = 48;
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 24px; }
+svg text { font-size: 12px; dominant-baseline: middle; }
+rect.op { fill: #d9d9d9; stroke: #737373; }
+rect.linearized { fill: #a1d99b; stroke: #31a354; }
+rect.unlinearized { fill: #fc9272; stroke: #de2d26; stroke-width: 2; }
+line.lane { stroke: #f0f0f0; }
+line.marker { stroke: #252525; stroke-width: 2; }
+span.key { display: inline-block; width: 12px; height: 12px; margin: 0 4px 0 12px; }
+pre { background: #f7f7f7; padding: 8px; }";
+
+/// Escape text for embedding in HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Key identifying an event of a history, for matching against the events
+/// listed in a violation.
+fn event_key(node: Node, span: &OpSpan) -> (Node, Timestamp, Timestamp) {
+    (node, span.ts_req, span.ts_ack)
+}
+
+impl History {
+    /// Write a self-contained HTML report drawing the history as one lane
+    /// per node with each op a bar between its request and ack timestamps.
+    /// If `violation` is given, the longest linearizable prefix is
+    /// highlighted and the ops that could not be linearized are marked.
+    pub fn write_html(
+        &self,
+        violation: Option<&Violation>,
+        mut writer: impl Write,
+    ) -> io::Result<()> {
+        let (linearized, unlinearized): (HashSet<_>, HashSet<_>) = match violation {
+            Some(violation) => (
+                violation
+                    .linearized
+                    .iter()
+                    .map(|(node, span)| event_key(*node, span))
+                    .collect(),
+                violation
+                    .unlinearized
+                    .iter()
+                    .map(|(node, span)| event_key(*node, span))
+                    .collect(),
+            ),
+            None => Default::default(),
+        };
+
+        let events = self.events();
+        let t0 = events
+            .iter()
+            .map(|(_, span)| span.ts_req)
+            .min()
+            .unwrap_or(0);
+        let t1 = events
+            .iter()
+            .map(|(_, span)| span.ts_ack)
+            .max()
+            .unwrap_or(0);
+        let x = |ts: Timestamp| {
+            MARGIN
+                + if t1 == t0 {
+                    0
+                } else {
+                    ((ts - t0) as u128 * AXIS_WIDTH as u128 / (t1 - t0) as u128) as u64
+                }
+        };
+        let num_nodes = self.num_nodes() as u64;
+        let height = (num_nodes + 1) * LANE_HEIGHT;
+
+        writeln!(writer, "<!DOCTYPE html>")?;
+        writeln!(writer, "<html>\n<head>\n<meta charset=\"utf-8\">")?;
+        writeln!(writer, "<title>linearize report</title>")?;
+        writeln!(writer, "<style>\n{}\n</style>\n</head>\n<body>", STYLE)?;
+        match violation {
+            Some(violation) => writeln!(
+                writer,
+                "<h2>Violation revealed by node {} {}</h2>",
+                violation.node,
+                escape(&format!("{:?}", violation.span))
+            )?,
+            None => writeln!(writer, "<h2>History of {} events</h2>", events.len())?,
+        }
+        writeln!(
+            writer,
+            "<p><span class=\"key\" style=\"background:#a1d99b\"></span>linearizable prefix\
+             <span class=\"key\" style=\"background:#fc9272\"></span>cannot be linearized\
+             <span class=\"key\" style=\"background:#d9d9d9\"></span>other ops</p>"
+        )?;
+
+        writeln!(
+            writer,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+            2 * MARGIN + AXIS_WIDTH,
+            height
+        )?;
+        for node in 0..num_nodes {
+            let y = node * LANE_HEIGHT + LANE_HEIGHT / 2;
+            writeln!(
+                writer,
+                "<line class=\"lane\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>",
+                MARGIN,
+                y,
+                MARGIN + AXIS_WIDTH,
+                y
+            )?;
+            writeln!(writer, "<text x=\"4\" y=\"{}\">n{}</text>", y, node)?;
+        }
+        for (node, span) in events {
+            let top = *node as u64 * LANE_HEIGHT + 4;
+            let title = escape(&format!("{} {:?}", node, span));
+            if !span.is_normal() {
+                let mark = if matches!(span.inputs, OpInputs::Stopped) {
+                    "stopped"
+                } else {
+                    "resumed"
+                };
+                writeln!(
+                    writer,
+                    "<line class=\"marker\" x1=\"{0}\" y1=\"{1}\" x2=\"{0}\" y2=\"{2}\">\
+                     <title>{3} {4}</title></line>",
+                    x(span.ts_req),
+                    top,
+                    top + LANE_HEIGHT - 8,
+                    title,
+                    mark
+                )?;
+                continue;
+            }
+            let key = event_key(*node, span);
+            let class = if unlinearized.contains(&key) {
+                "op unlinearized"
+            } else if linearized.contains(&key) {
+                "op linearized"
+            } else {
+                "op"
+            };
+            let (left, right) = (x(span.ts_req), x(span.ts_ack));
+            writeln!(
+                writer,
+                "<g><title>{}</title><rect class=\"{}\" x=\"{}\" y=\"{}\" width=\"{}\" \
+                 height=\"{}\" rx=\"3\"/><text x=\"{}\" y=\"{}\">{}</text></g>",
+                title,
+                class,
+                left,
+                top,
+                (right - left).max(2),
+                LANE_HEIGHT - 8,
+                left + 3,
+                top + LANE_HEIGHT / 2 - 4,
+                escape(&span.to_string())
+            )?;
+        }
+        let axis = num_nodes * LANE_HEIGHT + LANE_HEIGHT / 2;
+        writeln!(
+            writer,
+            "<text x=\"{}\" y=\"{}\">{}</text>\n\
+             <text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\n</svg>",
+            MARGIN,
+            axis,
+            t0,
+            MARGIN + AXIS_WIDTH,
+            axis,
+            t1
+        )?;
+
+        if let Some(violation) = violation {
+            writeln!(writer, "<pre>{}</pre>", escape(&violation.to_string()))?;
+        }
+        writeln!(writer, "</body>\n</html>")
+    }
+}
@@ -0,0 +1,443 @@
+//! Importer of Jepsen histories of a single register, written in EDN as a
+//! sequence (or one vector) of op maps:
+//!
+//! ```text
+//! {:type :invoke, :f :write, :value 3, :process 0, :time 1000, :index 0}
+//! {:type :invoke, :f :read, :value nil, :process 1, :time 1100, :index 1}
+//! {:type :ok, :f :write, :value 3, :process 0, :time 1500, :index 2}
+//! {:type :ok, :f :read, :value 3, :process 1, :time 1700, :index 3}
+//! {:type :invoke, :f :cas, :value [3 4], :process 0, :time 1800, :index 4}
+//! {:type :info, :f :cas, :value [3 4], :process 0, :time 1900, :index 5}
+//! ```
+//!
+//! Conversion rules:
+//!
+//! - each integer `:process` becomes a node, numbered in order of first
+//!   imported op; other processes (e.g., `:nemesis`) are ignored
+//! - an `:invoke` is paired with the next completion of the same process;
+//!   the position of an event in the history becomes its timestamp, as
+//!   Jepsen histories are already in real-time order
+//! - `:ok` ops of `:read`, `:write` and `:cas` become Get, Put and Cas spans
+//! - `:fail` ops definitely took no effect and are dropped
+//! - `:info` ops (and invokes never completed) are indeterminate, i.e.,
+//!   either took effect or did not; Jepsen retires the process after, so
+//!   its node is stopped right after:
+//!   - writes and cas become Put and Cas spans acked after the end of the
+//!     history, so they may take effect any time after invoked, or never be
+//!     observed at all
+//!   - since a cas can only take effect on its old value, one extra node
+//!     puts the old value of each indeterminate cas after all other ops,
+//!     letting the cas take effect there unobserved
+//!   - reads are dropped
+
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::{History, HistoryError, Node, OpSpan, Timestamp, Value};
+
+/// Minimal EDN value, enough for Jepsen op maps.
+#[derive(Debug, Clone, PartialEq)]
+enum Edn {
+    Nil,
+    Int(i64),
+    Keyword(String),
+
+    /// Any other scalar (string, boolean, symbol, etc.), not needed.
+    Other,
+
+    /// List, vector or set.
+    Seq(Vec<Edn>),
+
+    /// Map, with the line where it starts.
+    Map {
+        entries: Vec<(Edn, Edn)>,
+        line: usize,
+    },
+}
+
+impl Edn {
+    /// Look up a keyword key of a map.
+    fn get(&self, key: &str) -> Option<&Edn> {
+        match self {
+            Edn::Map { entries, .. } => entries
+                .iter()
+                .find(|(k, _)| matches!(k, Edn::Keyword(k) if k == key))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+/// Minimal EDN reader over a string, tracking the current line.
+struct EdnReader<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> EdnReader<'a> {
+    fn new(text: &'a str) -> Self {
+        EdnReader {
+            chars: text.chars().peekable(),
+            line: 1,
+        }
+    }
+
+    fn err(&self, msg: impl Into<String>) -> HistoryError {
+        HistoryError::Parse {
+            line: self.line,
+            msg: msg.into(),
+        }
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    /// Skip whitespace (commas included) and comments.
+    fn skip_blank(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c == ';' {
+                while self.next_char().is_some_and(|c| c != '\n') {}
+            } else if c.is_whitespace() || c == ',' {
+                self.next_char();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Skip blanks and discarded forms (`#_` followed by a form).
+    fn skip_discarded(&mut self) -> Result<(), HistoryError> {
+        loop {
+            self.skip_blank();
+            let mut ahead = self.chars.clone();
+            if (ahead.next(), ahead.next()) != (Some('#'), Some('_')) {
+                return Ok(());
+            }
+            self.next_char();
+            self.next_char();
+            self.read_form()?;
+        }
+    }
+
+    /// Read the next form, or `None` at end of input.
+    fn read(&mut self) -> Result<Option<Edn>, HistoryError> {
+        self.skip_discarded()?;
+        let Some(&c) = self.chars.peek() else {
+            return Ok(None);
+        };
+        let form = match c {
+            '(' | '[' => {
+                self.next_char();
+                Edn::Seq(self.read_until(if c == '(' { ')' } else { ']' })?)
+            }
+            '{' => {
+                let line = self.line;
+                self.next_char();
+                let items = self.read_until('}')?;
+                if items.len() % 2 != 0 {
+                    return Err(self.err("map with odd number of forms"));
+                }
+                let mut items = items.into_iter();
+                let mut entries = vec![];
+                while let (Some(k), Some(v)) = (items.next(), items.next()) {
+                    entries.push((k, v));
+                }
+                Edn::Map { entries, line }
+            }
+            ')' | ']' | '}' => return Err(self.err(format!("unexpected '{}'", c))),
+            '"' => {
+                self.next_char();
+                loop {
+                    match self.next_char() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            self.next_char();
+                        }
+                        Some(_) => {}
+                        None => return Err(self.err("unterminated string")),
+                    }
+                }
+                Edn::Other
+            }
+            '#' => {
+                self.next_char();
+                match self.chars.peek() {
+                    Some('{') => {
+                        self.next_char();
+                        Edn::Seq(self.read_until('}')?)
+                    }
+                    _ => {
+                        // tagged literal, keep the value only
+                        self.read_token();
+                        self.read_form()?
+                    }
+                }
+            }
+            _ => {
+                let token = self.read_token();
+                match token.as_str() {
+                    "nil" => Edn::Nil,
+                    _ if token.starts_with(':') => Edn::Keyword(token[1..].into()),
+                    _ => match token.trim_end_matches('N').parse() {
+                        Ok(n) => Edn::Int(n),
+                        Err(_) => Edn::Other,
+                    },
+                }
+            }
+        };
+        Ok(Some(form))
+    }
+
+    /// Read the next form, which must exist.
+    fn read_form(&mut self) -> Result<Edn, HistoryError> {
+        self.read()?
+            .ok_or_else(|| self.err("unexpected end of input"))
+    }
+
+    /// Read forms until the given closing delimiter.
+    fn read_until(&mut self, close: char) -> Result<Vec<Edn>, HistoryError> {
+        let mut forms = vec![];
+        loop {
+            self.skip_discarded()?;
+            match self.chars.peek() {
+                Some(&c) if c == close => {
+                    self.next_char();
+                    return Ok(forms);
+                }
+                Some(_) => forms.push(self.read_form()?),
+                None => return Err(self.err(format!("missing '{}'", close))),
+            }
+        }
+    }
+
+    /// Read a token up to the next whitespace or delimiter.
+    fn read_token(&mut self) -> String {
+        let mut token = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || ",;()[]{}\"".contains(c) {
+                break;
+            }
+            token.push(c);
+            self.next_char();
+        }
+        token
+    }
+}
+
+/// Kind of a register op in Jepsen.
+#[derive(Debug, Clone, Copy)]
+enum JepsenFn {
+    Read,
+    Write(Value),
+    Cas(Value, Value),
+}
+
+impl History {
+    /// Read a Jepsen history of a single register in EDN, converting it as
+    /// described in the module docs.
+    pub fn read_jepsen(mut reader: impl BufRead) -> Result<Self, HistoryError> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+
+        // collect op maps, either top-level or inside top-level sequences
+        let mut edn = EdnReader::new(&text);
+        let mut ops = vec![];
+        while let Some(form) = edn.read()? {
+            match form {
+                Edn::Map { .. } => ops.push(form),
+                Edn::Seq(forms) => ops.extend(forms),
+                _ => return Err(edn.err("expected op map")),
+            }
+        }
+
+        let mut importer = Importer {
+            history: History::new(),
+            nodes: HashMap::new(),
+            end: ops.len() as Timestamp,
+            indeterminate: vec![],
+        };
+        let mut invoked: HashMap<i64, (Timestamp, JepsenFn, usize)> = HashMap::new();
+
+        for (i, op) in ops.iter().enumerate() {
+            let line = match op {
+                Edn::Map { line, .. } => *line,
+                _ => return Err(edn.err("expected op map")),
+            };
+            let parse_err = |msg: String| HistoryError::Parse { line, msg };
+            let process = match op.get("process") {
+                Some(Edn::Int(process)) => *process,
+                _ => continue,
+            };
+            let ts = i as Timestamp + 1;
+
+            match op.get("type") {
+                Some(Edn::Keyword(t)) if t == "invoke" => {
+                    let f = parse_fn(op).map_err(parse_err)?;
+                    if invoked.insert(process, (ts, f, line)).is_some() {
+                        return Err(parse_err(format!(
+                            "process {} invoked again before completion",
+                            process
+                        )));
+                    }
+                }
+                Some(Edn::Keyword(t)) if t == "ok" || t == "fail" || t == "info" => {
+                    let (ts_req, f, _) = invoked.remove(&process).ok_or_else(|| {
+                        parse_err(format!("completion without invoke on process {}", process))
+                    })?;
+                    match t.as_str() {
+                        "ok" => {
+                            let span = match f {
+                                JepsenFn::Read => {
+                                    OpSpan::get(parse_read(op).map_err(parse_err)?, ts_req, ts)
+                                }
+                                JepsenFn::Write(val) => OpSpan::put(val, ts_req, ts),
+                                JepsenFn::Cas(old, new) => OpSpan::cas(old, new, ts_req, ts),
+                            };
+                            importer.push(process, span, line)?;
+                        }
+                        "info" => importer.give_up(process, f, ts_req, line),
+                        _ => {} // failed op took no effect
+                    }
+                }
+                _ => return Err(parse_err("missing or unknown :type".into())),
+            }
+        }
+
+        // invokes never completed are indeterminate as well
+        let mut pending: Vec<_> = invoked.into_iter().collect();
+        pending.sort_by_key(|(_, (ts_req, _, _))| *ts_req);
+        for (process, (ts_req, f, line)) in pending {
+            importer.give_up(process, f, ts_req, line);
+        }
+
+        importer.finish()
+    }
+}
+
+/// State of converting Jepsen ops into a history.
+struct Importer {
+    history: History,
+
+    /// Node ID assigned to each process.
+    nodes: HashMap<i64, Node>,
+
+    /// Timestamp of the last event in the Jepsen history.
+    end: Timestamp,
+
+    /// Indeterminate ops, as (node, op, request timestamp, line), pushed
+    /// after the end of the history.
+    indeterminate: Vec<(Node, JepsenFn, Timestamp, usize)>,
+}
+
+impl Importer {
+    /// Get the node ID of a process, assigning one on first use.
+    fn node(&mut self, process: i64) -> Node {
+        let num_nodes = self.nodes.len();
+        *self.nodes.entry(process).or_insert(num_nodes)
+    }
+
+    /// Push a converted span onto the node of given process.
+    fn push(&mut self, process: i64, span: OpSpan, line: usize) -> Result<(), HistoryError> {
+        let node = self.node(process);
+        self.push_node(node, span, line)
+    }
+
+    /// Push a converted span onto given node.
+    fn push_node(&mut self, node: Node, span: OpSpan, line: usize) -> Result<(), HistoryError> {
+        self.history
+            .push(node, span)
+            .map_err(|e| HistoryError::Parse {
+                line,
+                msg: e.to_string(),
+            })
+    }
+
+    /// Record an indeterminate op, to be pushed once the end of the history
+    /// is reached.
+    fn give_up(&mut self, process: i64, f: JepsenFn, ts_req: Timestamp, line: usize) {
+        let node = self.node(process);
+        self.indeterminate.push((node, f, ts_req, line));
+    }
+
+    /// Push the indeterminate ops, which may take effect any time until the
+    /// end of the history (or, being the last in real time, never be
+    /// observed), each followed by stopping its node. Before them, an extra
+    /// node puts the old value of each indeterminate cas after all other
+    /// ops, so that a cas not taking effect before the end of the history
+    /// can still take effect where no op observes it.
+    fn finish(mut self) -> Result<History, HistoryError> {
+        let olds: Vec<(Value, usize)> = self
+            .indeterminate
+            .iter()
+            .filter_map(|&(_, f, _, line)| match f {
+                JepsenFn::Cas(old, _) => Some((old, line)),
+                _ => None,
+            })
+            .collect();
+        let extra = self.nodes.len();
+        let mut ts = self.end;
+        for &(old, line) in &olds {
+            self.push_node(extra, OpSpan::put(old, ts + 1, ts + 2), line)?;
+            ts += 2;
+        }
+
+        for (node, f, ts_req, line) in std::mem::take(&mut self.indeterminate) {
+            match f {
+                JepsenFn::Read => {}
+                JepsenFn::Write(val) => {
+                    self.push_node(node, OpSpan::put(val, ts_req, ts + 1), line)?
+                }
+                JepsenFn::Cas(old, new) => {
+                    self.push_node(node, OpSpan::cas(old, new, ts_req, ts + 1), line)?
+                }
+            }
+            self.push_node(node, OpSpan::stopped(ts + 2), line)?;
+            ts += 2;
+        }
+        if let Some(&(_, line)) = olds.last() {
+            self.push_node(extra, OpSpan::stopped(ts + 1), line)?;
+        }
+        Ok(self.history)
+    }
+}
+
+/// Convert an EDN integer into a value.
+fn to_value(edn: &Edn) -> Result<Value, String> {
+    match edn {
+        Edn::Int(n) if *n >= 0 => Ok(*n as Value),
+        Edn::Int(n) => Err(format!("negative register value {}", n)),
+        _ => Err("unsupported register value".into()),
+    }
+}
+
+/// Parse the `:f` and `:value` of an invoke.
+fn parse_fn(op: &Edn) -> Result<JepsenFn, String> {
+    let value = op.get("value").unwrap_or(&Edn::Nil);
+    match op.get("f") {
+        Some(Edn::Keyword(f)) if f == "read" => Ok(JepsenFn::Read),
+        Some(Edn::Keyword(f)) if f == "write" => Ok(JepsenFn::Write(to_value(value)?)),
+        Some(Edn::Keyword(f)) if f == "cas" => match value {
+            Edn::Seq(pair) if pair.len() == 2 => {
+                Ok(JepsenFn::Cas(to_value(&pair[0])?, to_value(&pair[1])?))
+            }
+            _ => Err("cas value must be a pair [old new]".into()),
+        },
+        Some(Edn::Keyword(f)) => Err(format!("unsupported :f :{}", f)),
+        _ => Err("missing :f".into()),
+    }
+}
+
+/// Parse the `:value` returned by a read.
+fn parse_read(op: &Edn) -> Result<Option<Value>, String> {
+    match op.get("value") {
+        None | Some(Edn::Nil) => Ok(None),
+        Some(value) => to_value(value).map(Some),
+    }
+}
@@ -0,0 +1,150 @@
+//! JSON Lines format of histories, enabled by the `serde` feature.
+//!
+//! Each non-empty line is one JSON object describing one event, in the order
+//! fed. Fields:
+//!
+//! - `node`: node ID, starting from 0
+//! - `type`: one of `put`, `get`, `cas`, `fail`, `stopped`, `resumed`
+//! - `value`: value written by a `put` or `cas`, or value returned by a `get`
+//!   (`null` or absent for nil); absent for other types
+//! - `old`: value found by a `cas`; absent for other types
+//! - `ts_req`: starting timestamp (the only timestamp of `stopped` and
+//!   `resumed`)
+//! - `ts_ack`: finish timestamp; optional for `stopped` and `resumed`
+//!
+//! Timestamps are plain integers by default, or in the serialized form of
+//! any other `Clock` type, e.g. `{"physical":1,"logical":0}` or `[1,0,2]`.
+//!
+//! ```text
+//! {"node":0,"type":"put","value":8,"ts_req":100,"ts_ack":105}
+//! {"node":1,"type":"get","value":null,"ts_req":101,"ts_ack":103}
+//! {"node":0,"type":"cas","old":8,"value":9,"ts_req":106,"ts_ack":108}
+//! {"node":1,"type":"fail","ts_req":104,"ts_ack":107}
+//! {"node":0,"type":"stopped","ts_req":109}
+//! ```
+
+use std::io::{BufRead, Write};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{Clock, History, HistoryError, Node, OpInputs, OpResult, OpSpan, Value};
+
+/// Type of an event.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum EventType {
+    Put,
+    Get,
+    Cas,
+    Fail,
+    Stopped,
+    Resumed,
+}
+
+/// One event of the JSON Lines format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JsonEvent<T> {
+    node: Node,
+
+    #[serde(rename = "type")]
+    kind: EventType,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<Value>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    old: Option<Value>,
+
+    ts_req: T,
+
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    ts_ack: Option<T>,
+}
+
+impl<T: Clock> JsonEvent<T> {
+    /// Convert from an event of the history.
+    fn from_event(node: Node, span: &OpSpan<T>) -> Self {
+        let (kind, value, old) = match (&span.inputs, &span.result) {
+            (OpInputs::Put { val }, _) => (EventType::Put, Some(*val), None),
+            (OpInputs::Get, OpResult::Get { val }) => (EventType::Get, *val, None),
+            (OpInputs::Cas { old, new }, _) => (EventType::Cas, Some(*new), Some(*old)),
+            (OpInputs::Get, _) | (OpInputs::Fail, _) => (EventType::Fail, None, None),
+            (OpInputs::Stopped, _) => (EventType::Stopped, None, None),
+            (OpInputs::Resumed, _) => (EventType::Resumed, None, None),
+        };
+        JsonEvent {
+            node,
+            kind,
+            value,
+            old,
+            ts_req: span.ts_req.clone(),
+            ts_ack: if span.is_normal() {
+                Some(span.ts_ack.clone())
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Convert into an event of the history.
+    fn into_event(self) -> Result<(Node, OpSpan<T>), String> {
+        let span = match self.kind {
+            EventType::Stopped => OpSpan::stopped(self.ts_req),
+            EventType::Resumed => OpSpan::resumed(self.ts_req),
+            kind => {
+                let ts_ack = self.ts_ack.ok_or("missing ts_ack")?;
+                if !self.ts_req.happens_before(&ts_ack) {
+                    return Err("ts_req must happen before ts_ack".into());
+                }
+                match kind {
+                    EventType::Put => {
+                        OpSpan::put(self.value.ok_or("missing value")?, self.ts_req, ts_ack)
+                    }
+                    EventType::Get => OpSpan::get(self.value, self.ts_req, ts_ack),
+                    EventType::Cas => OpSpan::cas(
+                        self.old.ok_or("missing old")?,
+                        self.value.ok_or("missing value")?,
+                        self.ts_req,
+                        ts_ack,
+                    ),
+                    _ => OpSpan::fail(self.ts_req, ts_ack),
+                }
+            }
+        };
+        Ok((self.node, span))
+    }
+}
+
+impl<T: Clock + DeserializeOwned> History<T> {
+    /// Read a history in the JSON Lines format.
+    pub fn read_jsonl(reader: impl BufRead) -> Result<Self, HistoryError> {
+        let mut history = History::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let parse_err = |msg: String| HistoryError::Parse { line: i + 1, msg };
+            let event: JsonEvent<T> =
+                serde_json::from_str(&line).map_err(|e| parse_err(e.to_string()))?;
+            let (node, span) = event.into_event().map_err(parse_err)?;
+            history
+                .push(node, span)
+                .map_err(|e| parse_err(e.to_string()))?;
+        }
+        Ok(history)
+    }
+}
+
+impl<T: Clock + Serialize> History<T> {
+    /// Write the history in the JSON Lines format.
+    pub fn write_jsonl(&self, mut writer: impl Write) -> Result<(), HistoryError> {
+        for (node, span) in self.events() {
+            serde_json::to_writer(&mut writer, &JsonEvent::from_event(*node, span))
+                .map_err(|e| HistoryError::Invalid(e.to_string()))?;
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
@@ -1,9 +1,74 @@
+mod clock;
+pub use clock::{Clock, HybridTimestamp, VectorTimestamp};
+
 mod operation;
 pub use operation::{Node, OpInputs, OpResult, OpSpan, Timestamp, Value};
 
+mod model;
+pub use model::ConsistencyModel;
+
+mod violation;
+pub use violation::Violation;
+
+mod limits;
+pub use limits::{LimitPolicy, Limits, Verdict};
+
+mod stats;
+pub use stats::Stats;
+
+mod observer;
+pub use observer::{CheckerObserver, PruneReason, StateView};
+
+mod dot;
+pub use dot::DotObserver;
+
 mod linearizer;
 pub use linearizer::Linearizer;
 
+mod session;
+pub use session::{SessionChecker, SessionGuarantee, SessionReport};
+
+mod recorder;
+pub use recorder::{Recorder, RecorderClock};
+
+mod shared;
+pub use shared::SharedLinearizer;
+
+#[cfg(feature = "async")]
+mod service;
+#[cfg(feature = "async")]
+pub use service::{CheckerService, SpanSender};
+
+mod history;
+pub use history::{History, HistoryError};
+
+mod binary;
+pub use binary::{BinaryReader, BinaryWriter};
+
+mod html;
+mod timeline;
+
+mod generator;
+pub use generator::{Faults, HistoryGenerator};
+
+#[cfg(any(test, feature = "proptest"))]
+mod brute_force;
+
+#[cfg(feature = "proptest")]
+mod arbitrary;
+#[cfg(feature = "proptest")]
+pub use arbitrary::{arb_events, arb_history};
+
+#[cfg(feature = "serde")]
+mod jsonl;
+#[cfg(feature = "serde")]
+mod porcupine;
+
+#[cfg(feature = "serde")]
+mod snapshot;
+
+mod jepsen;
+
 mod possibility;
 use possibility::Possibility;
 
@@ -0,0 +1,62 @@
+//! Resource limits on the frontier of possibilities, and the verdicts that
+//! result when they are hit.
+
+use std::fmt;
+use std::time::Duration;
+
+/// Policy when any of the `Limits` is hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LimitPolicy {
+    /// Stop checking and drop all possibilities; the verdict becomes
+    /// `Unknown` for good.
+    #[default]
+    GiveUp,
+
+    /// Keep checking with only a sample of `size` possibilities (the ones
+    /// furthest ahead). A surviving possibility still proves the history
+    /// valid, but running out of them no longer proves a violation, so the
+    /// verdict becomes `Unknown` instead of `Violated` then.
+    Sample { size: usize },
+}
+
+/// Caps on resources used by a linearizer; `None` means unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Limits {
+    /// Maximum number of possibilities in the frontier.
+    pub max_possibilities: Option<usize>,
+
+    /// Maximum approximate memory taken by the frontier, in bytes.
+    pub max_memory: Option<usize>,
+
+    /// Maximum time spent stepping within one `feed_span()` call.
+    pub max_step_time: Option<Duration>,
+
+    /// What to do when any of the caps is hit.
+    pub policy: LimitPolicy,
+}
+
+/// Verdict of a check so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Verdict {
+    /// The ops fed so far satisfy the consistency model.
+    Pass,
+
+    /// The ops fed so far definitely violate the consistency model.
+    Violated,
+
+    /// Inconclusive, because a limit was hit.
+    Unknown,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Violated => write!(f, "violated"),
+            Verdict::Unknown => write!(f, "unknown"),
+        }
+    }
+}
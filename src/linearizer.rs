@@ -1,28 +1,192 @@
 //! Simple on-line linearizability checker.
 
+use std::cmp;
 use std::collections::HashSet;
 use std::fmt;
 use std::mem;
+use std::time::{Duration, Instant};
 
-use crate::{Node, OpSpan, Possibility};
+use crate::{
+    CheckerObserver, Clock, ConsistencyModel, LimitPolicy, Limits, Node, OpSpan, Possibility,
+    StateView, Stats, Timestamp, Verdict, Violation,
+};
 
-/// On-line per-object linearizability checker.
+/// On-line per-object linearizability checker, generic over the type of
+/// timestamps of operation spans (physical `Timestamp` by default).
 #[derive(Debug, Clone)]
-pub struct Linearizer {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Linearizer<T: Clock = Timestamp> {
     /// Number of nodes.
     pub(crate) num_nodes: usize,
 
+    /// Consistency model to check against.
+    pub(crate) model: ConsistencyModel,
+
+    /// Maximum skew between physical clocks of different nodes.
+    pub(crate) max_clock_skew: u64,
+
     /// Collection of currently possible correct states.
-    pub(crate) possibilities: HashSet<Possibility>,
+    pub(crate) possibilities: HashSet<Possibility<T>>,
+
+    /// Report of the violation, if detected.
+    pub(crate) violation: Option<Violation<T>>,
+
+    /// Resource limits on the frontier.
+    pub(crate) limits: Limits,
+
+    /// Set if checking was given up on hitting a limit.
+    pub(crate) gave_up: bool,
+
+    /// Set if the frontier has ever been cut down to a sample.
+    pub(crate) sampled: bool,
+
+    /// Log of settled ops: the common prefix of all possibilities' lineage,
+    /// which can no longer change. Only kept if `keep_settled` is set.
+    pub(crate) settled: Vec<(Node, OpSpan<T>)>,
+
+    /// Whether to keep settled ops in the log or discard them.
+    pub(crate) keep_settled: bool,
+
+    /// Total number of ops settled so far.
+    pub(crate) num_settled: usize,
+
+    /// Total number of spans fed in so far.
+    pub(crate) spans_fed: usize,
+
+    /// Largest number of possibilities so far.
+    pub(crate) peak_possibilities: usize,
+
+    /// Total time spent stepping possibilities.
+    pub(crate) step_time: Duration,
 }
 
-impl Linearizer {
+impl<T: Clock> Linearizer<T> {
     /// Create a new linearizer with just one empty initial state to start with.
     pub fn new(num_nodes: usize) -> Self {
         assert_ne!(num_nodes, 0);
         Linearizer {
             num_nodes,
+            model: ConsistencyModel::default(),
+            max_clock_skew: 0,
             possibilities: HashSet::from([Possibility::initial(num_nodes)]),
+            violation: None,
+            limits: Limits::default(),
+            gave_up: false,
+            sampled: false,
+            settled: vec![],
+            keep_settled: false,
+            num_settled: 0,
+            spans_fed: 0,
+            peak_possibilities: 1,
+            step_time: Duration::ZERO,
+        }
+    }
+
+    /// Set the consistency model to check against. Should be called before
+    /// feeding in any operation spans.
+    pub fn with_model(mut self, model: ConsistencyModel) -> Self {
+        if let ConsistencyModel::KAtomic { k } = model {
+            assert_ne!(k, 0);
+        }
+        self.model = model;
+        self
+    }
+
+    /// Get the consistency model being checked against.
+    pub fn model(&self) -> ConsistencyModel {
+        self.model
+    }
+
+    /// Set the maximum skew between physical clocks of different nodes. Two
+    /// ops from different nodes are then ordered in real time only if one
+    /// started at least `max_clock_skew` after the other finished. Should be
+    /// called before feeding in any operation spans.
+    pub fn with_max_clock_skew(mut self, max_clock_skew: u64) -> Self {
+        self.max_clock_skew = max_clock_skew;
+        self
+    }
+
+    /// Get the maximum skew between physical clocks of different nodes.
+    pub fn max_clock_skew(&self) -> u64 {
+        self.max_clock_skew
+    }
+
+    /// Set resource limits on the frontier of possibilities, and the policy
+    /// when any of them is hit.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        if let LimitPolicy::Sample { size } = limits.policy {
+            assert_ne!(size, 0);
+        }
+        self.limits = limits;
+        self
+    }
+
+    /// Get the resource limits.
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Set whether to keep settled ops (the prefix of the linearization that
+    /// all possibilities agree on) in a log, or discard them to keep memory
+    /// flat during unbounded on-line checking. Discarded by default; keep
+    /// them if a full witness or violation report is wanted.
+    pub fn with_keep_settled(mut self, keep_settled: bool) -> Self {
+        self.keep_settled = keep_settled;
+        self
+    }
+
+    /// Get the log of settled ops not yet taken.
+    pub fn settled(&self) -> &[(Node, OpSpan<T>)] {
+        &self.settled
+    }
+
+    /// Take the log of settled ops, e.g., for persisting it elsewhere.
+    pub fn take_settled(&mut self) -> Vec<(Node, OpSpan<T>)> {
+        mem::take(&mut self.settled)
+    }
+
+    /// Get the total number of ops settled so far, including the ones
+    /// discarded or taken.
+    pub fn num_settled(&self) -> usize {
+        self.num_settled
+    }
+
+    /// Get statistics of the checker so far. Computing queue depths takes
+    /// time linear in the number of possibilities.
+    pub fn stats(&self) -> Stats {
+        let mut max_queue_depths = vec![0; self.num_nodes];
+        let mut avg_queue_depths = vec![0.0; self.num_nodes];
+        for possibility in &self.possibilities {
+            for node in 0..self.num_nodes {
+                let len = possibility.queue_len(node);
+                max_queue_depths[node] = max_queue_depths[node].max(len);
+                avg_queue_depths[node] += len as f64;
+            }
+        }
+        if !self.possibilities.is_empty() {
+            for avg in &mut avg_queue_depths {
+                *avg /= self.possibilities.len() as f64;
+            }
+        }
+        Stats {
+            num_possibilities: self.possibilities.len(),
+            peak_possibilities: self.peak_possibilities,
+            max_queue_depths,
+            avg_queue_depths,
+            spans_fed: self.spans_fed,
+            ops_settled: self.num_settled,
+            step_time: self.step_time,
+        }
+    }
+
+    /// Get the verdict after the spans fed so far.
+    pub fn verdict(&self) -> Verdict {
+        if self.gave_up || (self.possibilities.is_empty() && self.sampled) {
+            Verdict::Unknown
+        } else if self.possibilities.is_empty() {
+            Verdict::Violated
+        } else {
+            Verdict::Pass
         }
     }
 
@@ -31,15 +195,54 @@ impl Linearizer {
     ///
     /// Returns true if still have possibilities left after stepping attempt;
     /// otherwise returns false, meaning linearizability has been violated.
-    pub fn feed_span(&mut self, node: Node, span: OpSpan) -> bool {
+    /// If a limit has been hit, `.verdict()` tells whether the result is
+    /// conclusive; giving up always returns true.
+    pub fn feed_span(&mut self, node: Node, span: OpSpan<T>) -> bool {
+        self.feed_span_with(node, span, &mut ())
+    }
+
+    /// Same as `.feed_span()`, calling the hooks of given observer on each
+    /// internal step.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(node = node, op = %span))
+    )]
+    pub fn feed_span_with(
+        &mut self,
+        node: Node,
+        span: OpSpan<T>,
+        observer: &mut impl CheckerObserver<T>,
+    ) -> bool {
         assert!(node < self.num_nodes);
-        assert!((span.ts_ack > span.ts_req) || (!span.is_normal()));
+        assert!(span.ts_req.happens_before(&span.ts_ack) || (!span.is_normal()));
+        self.spans_fed += 1;
+        observer.on_feed(node, &span);
 
         if self.possibilities.is_empty() {
-            // already violated, always return false
-            return false;
+            // already violated or given up, verdict won't change
+            return self.verdict() != Verdict::Violated;
         }
+        let start = Instant::now();
+        let ok = self.feed_and_step(node, span, start, observer);
+        self.step_time += start.elapsed();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            frontier = self.possibilities.len(),
+            verdict = %self.verdict(),
+            "fed"
+        );
+        ok
+    }
 
+    /// Append the span to all possibilities and step them as far as
+    /// possible; see `feed_span()`.
+    fn feed_and_step(
+        &mut self,
+        node: Node,
+        span: OpSpan<T>,
+        start: Instant,
+        observer: &mut impl CheckerObserver<T>,
+    ) -> bool {
         // append the new span to all current states, then put all steppable
         // states into `pending` and others into `self.possibilities`
         let mut pending = HashSet::new();
@@ -56,29 +259,185 @@ impl Linearizer {
         }
         mem::swap(&mut self.possibilities, &mut new_pending);
 
-        // recursively call `.step()` until no pending states left
+        // recursively call `.step()` until no pending states left, keeping
+        // the dead end with the longest lineage for reporting violation
         //
         // TODO: for each feed, the loop below obviously has the potential to
         //       be parallelized to boost checker performance
+        let mut dead_end: Option<Possibility<T>> = None;
         while !pending.is_empty() {
-            for possibility in pending.drain() {
+            for mut possibility in pending.drain() {
                 debug_assert!(possibility.can_step());
-                for new_possibility in possibility.step() {
-                    if new_possibility.can_step() {
-                        new_pending.insert(new_possibility);
+                let new_possibilities = possibility.step(self.model, self.max_clock_skew, observer);
+                if new_possibilities.is_empty() {
+                    observer.on_dead_end(StateView(&possibility));
+                    if dead_end
+                        .as_ref()
+                        .is_none_or(|d| d.lineage().len() < possibility.lineage().len())
+                    {
+                        dead_end = Some(possibility);
+                    }
+                }
+                for new_possibility in new_possibilities {
+                    let old_possibility = if new_possibility.can_step() {
+                        new_pending.replace(new_possibility)
                     } else {
-                        self.possibilities.insert(new_possibility);
+                        self.possibilities.replace(new_possibility)
+                    };
+                    if let Some(old_possibility) = old_possibility {
+                        observer.on_deduplicated(StateView(&old_possibility));
                     }
                 }
             }
             mem::swap(&mut pending, &mut new_pending);
+            self.peak_possibilities = self
+                .peak_possibilities
+                .max(self.possibilities.len() + pending.len());
+
+            if self.exceeds_limits(&pending, start) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    frontier = self.possibilities.len() + pending.len(),
+                    policy = ?self.limits.policy,
+                    "limit hit"
+                );
+                match self.limits.policy {
+                    LimitPolicy::GiveUp => {
+                        self.gave_up = true;
+                        self.possibilities.clear();
+                        return true;
+                    }
+                    LimitPolicy::Sample { size } => {
+                        self.sampled = true;
+                        self.sample(&mut pending, size);
+                    }
+                }
+            }
         }
 
-        !self.possibilities.is_empty()
+        if self.possibilities.is_empty() {
+            #[cfg(feature = "tracing")]
+            tracing::info!(node, op = %span, "violation detected");
+            let dead_end = dead_end.unwrap();
+            self.violation = Some(Violation {
+                node,
+                span,
+                linearized: self
+                    .settled
+                    .iter()
+                    .chain(dead_end.lineage())
+                    .cloned()
+                    .collect(),
+                unlinearized: dead_end
+                    .queued()
+                    .map(|(node, span)| (node, span.clone()))
+                    .collect(),
+            });
+            self.verdict() != Verdict::Violated
+        } else {
+            self.settle(observer);
+            true
+        }
+    }
+
+    /// Move the common prefix of all possibilities' lineage into the settled
+    /// log (or discard it).
+    fn settle(&mut self, observer: &mut impl CheckerObserver<T>) {
+        // scan lineages op by op across all possibilities rather than one
+        // possibility at a time, so that the work done is proportional to
+        // how far the settled frontier advances (plus one mismatching op)
+        let first = self.possibilities.iter().next().unwrap().lineage();
+        let len = first
+            .iter()
+            .enumerate()
+            .take_while(|(i, (n1, s1))| {
+                self.possibilities.iter().all(|possibility| {
+                    // ops of a node are identified by their start timestamps
+                    possibility
+                        .lineage()
+                        .get(*i)
+                        .is_some_and(|(n2, s2)| n1 == n2 && s1.ts_req == s2.ts_req)
+                })
+            })
+            .count();
+        if len == 0 {
+            return;
+        }
+
+        // lineage is not part of a possibility's hash, so rebuilding the set
+        // keeps it intact
+        let mut prefix = None;
+        for mut possibility in mem::take(&mut self.possibilities) {
+            let settled = possibility.settle(len);
+            prefix.get_or_insert(settled);
+            self.possibilities.insert(possibility);
+        }
+        let prefix = prefix.unwrap();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(settled = len, "settled common prefix");
+        observer.on_settled(&prefix);
+        self.num_settled += len;
+        if self.keep_settled {
+            self.settled.extend(prefix);
+        }
+    }
+
+    /// Check if the frontier, including states still pending to step,
+    /// exceeds any of the limits.
+    fn exceeds_limits(&self, pending: &HashSet<Possibility<T>>, start: Instant) -> bool {
+        let limits = &self.limits;
+        limits
+            .max_possibilities
+            .is_some_and(|max| self.possibilities.len() + pending.len() > max)
+            || limits
+                .max_step_time
+                .is_some_and(|max| start.elapsed() > max)
+            || limits.max_memory.is_some_and(|max| {
+                self.possibilities
+                    .iter()
+                    .chain(pending.iter())
+                    .map(Possibility::approx_size)
+                    .sum::<usize>()
+                    > max
+            })
+    }
+
+    /// Cut the frontier, including states still pending to step, down to
+    /// the given number of states furthest ahead.
+    fn sample(&mut self, pending: &mut HashSet<Possibility<T>>, size: usize) {
+        let mut states: Vec<(bool, Possibility<T>)> = self
+            .possibilities
+            .drain()
+            .map(|p| (false, p))
+            .chain(pending.drain().map(|p| (true, p)))
+            .collect();
+        states.sort_by_key(|(_, p)| cmp::Reverse(p.lineage().len()));
+        for (steppable, possibility) in states.into_iter().take(size) {
+            if steppable {
+                pending.insert(possibility);
+            } else {
+                self.possibilities.insert(possibility);
+            }
+        }
+    }
+
+    /// Get the report of the violation, if one has been detected.
+    pub fn violation(&self) -> Option<&Violation<T>> {
+        self.violation.as_ref()
+    }
+
+    /// Get a witness of linearizability so far: one possible sequential order
+    /// of the ops linearized, or `None` if violated. Settled ops discarded
+    /// or taken are not included.
+    pub fn witness(&self) -> Option<Vec<(Node, OpSpan<T>)>> {
+        self.possibilities
+            .iter()
+            .max_by_key(|p| p.lineage().len())
+            .map(|p| self.settled.iter().chain(p.lineage()).cloned().collect())
     }
 }
 
-impl fmt::Display for Linearizer {
+impl<T: Clock> fmt::Display for Linearizer<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "Possibilities {{")?;
         for possibility in &self.possibilities {
@@ -0,0 +1,337 @@
+//! Command-line checker of recorded histories.
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use linearize::{
+    ConsistencyModel, DotObserver, History, HistoryError, LimitPolicy, Limits, Linearizer, OpSpan,
+    Verdict,
+};
+
+const USAGE: &str = "\
+Usage: linearize [OPTIONS] [FILE]
+
+Check a recorded history read from FILE (or stdin if absent or '-').
+Porcupine logs are checked key by key.
+
+Options:
+  -n, --nodes <N>             number of nodes [default: inferred]
+  -m, --model <MODEL>         consistency model to check against:
+                              linearizable, sequential, regular, safe,
+                              k-atomic=<K>, delta-atomic=<DELTA>
+                              [default: linearizable]
+  -f, --format <FORMAT>       history format: text, binary, jsonl,
+                              jepsen, porcupine [default: text]
+  -s, --max-clock-skew <S>    maximum clock skew between nodes [default: 0]
+  -p, --max-possibilities <N> give up beyond N possible states
+  --max-memory <BYTES>        give up beyond about BYTES of possible states
+  --max-step-time <MS>        give up if one event takes over MS to check
+  --sample <N>                on hitting a limit, keep checking with a
+                              sample of N possible states instead
+  -w, --witness               print a witness order if check passes
+  -r, --report                print a violation report if check fails
+  -t, --timeline              draw a timeline of the history, or of the
+                              ops involved if check fails
+  --html <FILE>               write an HTML report of the history to FILE
+                              (suffixed by key for partitioned histories)
+  --dot <FILE>                write the explored graph of possible states
+                              to FILE in Graphviz DOT format (suffixed
+                              likewise)
+  --dot-last <N>              only keep the graph of the last N events
+  --stats                     print checker statistics after checking
+  -h, --help                  print this help
+
+Exit status is 0 if check passes, 1 if violated, 2 on error, and 3 if
+inconclusive due to a limit.";
+
+/// Width of the time axis of drawn timelines.
+const TIMELINE_WIDTH: usize = 72;
+
+/// History file format.
+#[derive(Debug, Clone, Copy, Default)]
+enum Format {
+    #[default]
+    Text,
+    Binary,
+    Jsonl,
+    Jepsen,
+    Porcupine,
+}
+
+/// Command-line options.
+#[derive(Debug, Default)]
+struct Options {
+    num_nodes: Option<usize>,
+    format: Format,
+    model: ConsistencyModel,
+    max_clock_skew: u64,
+    limits: Limits,
+    witness: bool,
+    report: bool,
+    timeline: bool,
+    html: Option<String>,
+    dot: Option<String>,
+    dot_last: Option<usize>,
+    stats: bool,
+    file: Option<String>,
+}
+
+/// Parse a number given for an option.
+fn number<N: std::str::FromStr>(name: &str, value: String) -> Result<N, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid number '{}' for option '{}'", value, name))
+}
+
+impl Options {
+    /// Parse options from command-line arguments; returns `Ok(None)` if
+    /// help is requested.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Options::default();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or(format!("missing value for option '{}'", name))
+            };
+            match arg.as_str() {
+                "-n" | "--nodes" => options.num_nodes = Some(number(&arg, value(&arg)?)?),
+                "-m" | "--model" => options.model = value(&arg)?.parse()?,
+                "-f" | "--format" => {
+                    options.format = match value(&arg)?.as_str() {
+                        "text" => Format::Text,
+                        "binary" => Format::Binary,
+                        "jsonl" => Format::Jsonl,
+                        "jepsen" => Format::Jepsen,
+                        "porcupine" => Format::Porcupine,
+                        format => return Err(format!("unknown format '{}'", format)),
+                    }
+                }
+                "-s" | "--max-clock-skew" => options.max_clock_skew = number(&arg, value(&arg)?)?,
+                "-p" | "--max-possibilities" => {
+                    options.limits.max_possibilities = Some(number(&arg, value(&arg)?)?);
+                }
+                "--max-memory" => {
+                    options.limits.max_memory = Some(number(&arg, value(&arg)?)?);
+                }
+                "--max-step-time" => {
+                    let ms = number(&arg, value(&arg)?)?;
+                    options.limits.max_step_time = Some(Duration::from_millis(ms));
+                }
+                "--sample" => {
+                    let size = number(&arg, value(&arg)?)?;
+                    if size == 0 {
+                        return Err("sample size must be positive".into());
+                    }
+                    options.limits.policy = LimitPolicy::Sample { size };
+                }
+                "-w" | "--witness" => options.witness = true,
+                "-r" | "--report" => options.report = true,
+                "-t" | "--timeline" => options.timeline = true,
+                "--html" => options.html = Some(value(&arg)?),
+                "--dot" => options.dot = Some(value(&arg)?),
+                "--dot-last" => options.dot_last = Some(number(&arg, value(&arg)?)?),
+                "--stats" => options.stats = true,
+                "-h" | "--help" => return Ok(None),
+                _ if arg.starts_with('-') && arg != "-" => {
+                    return Err(format!("unknown option '{}'", arg));
+                }
+                _ if options.file.is_some() => {
+                    return Err(format!("unexpected argument '{}'", arg));
+                }
+                _ => options.file = Some(arg),
+            }
+        }
+        Ok(Some(options))
+    }
+}
+
+/// Read the histories in given format from the given file or stdin, each
+/// labeled by its key (empty unless the format is partitioned by keys).
+fn read_histories(
+    file: Option<&str>,
+    format: Format,
+) -> Result<Vec<(String, History)>, HistoryError> {
+    let reader: Box<dyn BufRead> = match file {
+        None | Some("-") => Box::new(io::stdin().lock()),
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+    };
+    let history = match format {
+        Format::Text => History::read_text(reader)?,
+        Format::Binary => History::read_binary(reader)?,
+        Format::Jepsen => History::read_jepsen(reader)?,
+        #[cfg(feature = "serde")]
+        Format::Jsonl => History::read_jsonl(reader)?,
+        #[cfg(feature = "serde")]
+        Format::Porcupine => return Ok(History::read_porcupine(reader)?.into_iter().collect()),
+        #[cfg(not(feature = "serde"))]
+        Format::Jsonl | Format::Porcupine => {
+            return Err(HistoryError::Invalid(
+                "jsonl and porcupine formats require the serde feature".into(),
+            ))
+        }
+    };
+    Ok(vec![(String::new(), history)])
+}
+
+/// Path of an output file for the history of given label: the given path,
+/// with the label appended to the file stem if not empty.
+fn output_path(path: &str, label: &str) -> PathBuf {
+    let mut path = PathBuf::from(path);
+    if !label.is_empty() {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let mut name = format!("{}-{}", stem, label);
+        if let Some(ext) = path.extension() {
+            name = format!("{}.{}", name, ext.to_string_lossy());
+        }
+        path.set_file_name(name);
+    }
+    path
+}
+
+/// Check one history, printing the verdict prefixed by its label if any.
+fn check(label: &str, mut history: History, options: &Options) -> Result<Verdict, String> {
+    let prefix = if label.is_empty() {
+        String::new()
+    } else {
+        format!("{}: ", label)
+    };
+    let num_nodes = options.num_nodes.unwrap_or(history.num_nodes()).max(1);
+    if history.num_nodes() > num_nodes {
+        return Err(format!(
+            "{}history has {} nodes, more than {} given",
+            prefix,
+            history.num_nodes(),
+            num_nodes
+        ));
+    }
+    history.close();
+
+    let mut linearizer = Linearizer::new(num_nodes)
+        .with_model(options.model)
+        .with_max_clock_skew(options.max_clock_skew)
+        .with_limits(options.limits)
+        .with_keep_settled(
+            options.witness || options.report || options.timeline || options.html.is_some(),
+        );
+    // nodes given but absent from the history must not block checking
+    for node in history.num_nodes()..num_nodes {
+        linearizer.feed_span(node, OpSpan::stopped(0));
+    }
+
+    let mut observer = DotObserver::new();
+    if let Some(n) = options.dot_last {
+        observer = observer.with_last_feeds(n);
+    }
+    let violated_at = if options.dot.is_some() {
+        history
+            .events()
+            .iter()
+            .position(|(node, span)| !linearizer.feed_span_with(*node, span.clone(), &mut observer))
+    } else {
+        history.feed_into(&mut linearizer)
+    };
+    match (linearizer.verdict(), violated_at) {
+        (Verdict::Unknown, _) => {
+            println!(
+                "{}UNKNOWN: limit hit within {} events, {}",
+                prefix,
+                history.events().len(),
+                options.model
+            );
+        }
+        (_, None) => {
+            println!(
+                "{}PASS: {} events, {}",
+                prefix,
+                history.events().len(),
+                options.model
+            );
+            if options.witness {
+                for (node, span) in linearizer.witness().unwrap() {
+                    println!("  {} {:?}", node, span);
+                }
+            }
+            if options.timeline {
+                print!("{}", history.timeline(TIMELINE_WIDTH));
+            }
+        }
+        (_, Some(index)) => {
+            println!(
+                "{}FAIL: violated at event {} of {}, {}",
+                prefix,
+                index + 1,
+                history.events().len(),
+                options.model
+            );
+            if options.report {
+                println!("{}", linearizer.violation().unwrap());
+            }
+            if options.timeline {
+                print!(
+                    "{}",
+                    linearizer.violation().unwrap().timeline(TIMELINE_WIDTH)
+                );
+            }
+        }
+    }
+    if options.stats {
+        println!("  {}", linearizer.stats());
+    }
+    if let Some(path) = &options.html {
+        let path = output_path(path, label);
+        File::create(&path)
+            .and_then(|file| history.write_html(linearizer.violation(), BufWriter::new(file)))
+            .map_err(|e| format!("{}cannot write report {}: {}", prefix, path.display(), e))?;
+    }
+    if let Some(path) = &options.dot {
+        let path = output_path(path, label);
+        File::create(&path)
+            .and_then(|file| observer.write_dot(BufWriter::new(file)))
+            .map_err(|e| format!("{}cannot write graph {}: {}", prefix, path.display(), e))?;
+    }
+    Ok(linearizer.verdict())
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let histories = match read_histories(options.file.as_deref(), options.format) {
+        Ok(histories) => histories,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut verdicts = vec![];
+    for (label, history) in histories {
+        match check(&label, history, &options) {
+            Ok(verdict) => verdicts.push(verdict),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                return ExitCode::from(2);
+            }
+        }
+    }
+    if verdicts.contains(&Verdict::Violated) {
+        ExitCode::from(1)
+    } else if verdicts.contains(&Verdict::Unknown) {
+        ExitCode::from(3)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,107 @@
+//! Consistency models that the checker can be configured to verify.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Consistency model enum, deciding which orderings of operations across
+/// nodes are considered valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConsistencyModel {
+    /// Linearizability (i.e., atomic register semantics): a valid global
+    /// sequential order must also respect the real-time order between
+    /// operations.
+    #[default]
+    Linearizable,
+
+    /// Sequential consistency: a valid global sequential order only needs
+    /// to preserve each node's own program order; real-time order across
+    /// different nodes is ignored.
+    Sequential,
+
+    /// k-atomicity: like linearizability, except that a Get may return any
+    /// of the last `k` values written in the global order (`k = 1` is
+    /// equivalent to linearizability).
+    KAtomic { k: usize },
+
+    /// Delta-atomicity: like linearizability, except that a Get may also
+    /// return a value overwritten by a write that finished no earlier than
+    /// `delta` units of physical time before the Get started.
+    DeltaAtomic { delta: u64 },
+
+    /// Regular register semantics: a Get may return the value of the last
+    /// write before it, or that of any write overlapping with it in time.
+    Regular,
+
+    /// Safe register semantics: a Get overlapping with any write in time
+    /// may return anything; otherwise it must return the value of the last
+    /// write before it.
+    Safe,
+}
+
+impl ConsistencyModel {
+    /// Check if the model enforces the real-time constraint across nodes.
+    pub fn is_real_time(&self) -> bool {
+        !matches!(self, ConsistencyModel::Sequential)
+    }
+
+    /// Check if the model allows Gets to return stale values.
+    pub fn is_stale(&self) -> bool {
+        match self {
+            ConsistencyModel::KAtomic { k } => *k > 1,
+            ConsistencyModel::DeltaAtomic { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Check if the model allows Gets overlapping with writes to return
+    /// values other than the current one.
+    pub fn is_overlap_tolerant(&self) -> bool {
+        matches!(self, ConsistencyModel::Regular | ConsistencyModel::Safe)
+    }
+}
+
+impl fmt::Display for ConsistencyModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsistencyModel::Linearizable => write!(f, "linearizable"),
+            ConsistencyModel::Sequential => write!(f, "sequential"),
+            ConsistencyModel::KAtomic { k } => write!(f, "k-atomic={}", k),
+            ConsistencyModel::DeltaAtomic { delta } => write!(f, "delta-atomic={}", delta),
+            ConsistencyModel::Regular => write!(f, "regular"),
+            ConsistencyModel::Safe => write!(f, "safe"),
+        }
+    }
+}
+
+/// Parses the same format as `Display`, e.g. `sequential` or `k-atomic=2`.
+impl FromStr for ConsistencyModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, param) = match s.split_once('=') {
+            Some((name, param)) => (name, Some(param)),
+            None => (s, None),
+        };
+        let param = |what: &str| -> Result<u64, String> {
+            let param = param.ok_or(format!("missing {} in '{}'", what, s))?;
+            param
+                .parse()
+                .map_err(|_| format!("invalid {} '{}'", what, param))
+        };
+        match name {
+            "linearizable" | "atomic" => Ok(ConsistencyModel::Linearizable),
+            "sequential" => Ok(ConsistencyModel::Sequential),
+            "k-atomic" => match param("k")? {
+                0 => Err("k must be positive".into()),
+                k => Ok(ConsistencyModel::KAtomic { k: k as usize }),
+            },
+            "delta-atomic" => Ok(ConsistencyModel::DeltaAtomic {
+                delta: param("delta")?,
+            }),
+            "regular" => Ok(ConsistencyModel::Regular),
+            "safe" => Ok(ConsistencyModel::Safe),
+            _ => Err(format!("unknown consistency model '{}'", s)),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Hooks for observing the checker's internal steps, e.g., for custom
+//! logging or visualizations.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::{Clock, Node, OpSpan, Possibility, Timestamp, Value};
+
+/// Reason of a possibility failing to apply a candidate op.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PruneReason {
+    /// A Get returned a value the possibility cannot justify.
+    GetMismatch,
+
+    /// A Cas found a value different from the possibility's current one.
+    CasMismatch,
+
+    /// The op's result does not match its inputs.
+    InvalidResult,
+}
+
+impl fmt::Display for PruneReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PruneReason::GetMismatch => write!(f, "get value mismatch"),
+            PruneReason::CasMismatch => write!(f, "cas value mismatch"),
+            PruneReason::InvalidResult => write!(f, "invalid op result"),
+        }
+    }
+}
+
+/// Read-only view of one possible state, passed to observers.
+#[derive(Clone, Copy)]
+pub struct StateView<'a, T: Clock = Timestamp>(pub(crate) &'a Possibility<T>);
+
+impl<T: Clock> StateView<'_, T> {
+    /// Get the current value: `None` if uncertain, `Some(None)` if nil.
+    pub fn current_value(&self) -> Option<Option<Value>> {
+        self.0.current_val()
+    }
+
+    /// Get the sequence of ops linearized in this state, excluding ops
+    /// already settled.
+    pub fn lineage(&self) -> &[(Node, OpSpan<T>)] {
+        self.0.lineage()
+    }
+
+    /// Get the number of nodes.
+    pub fn num_nodes(&self) -> usize {
+        self.0.num_nodes()
+    }
+
+    /// Get the number of ops queued from given node.
+    pub fn queue_len(&self, node: Node) -> usize {
+        self.0.queue_len(node)
+    }
+
+    /// Get a key identifying the state: states deduplicated into one have
+    /// the same key.
+    pub fn key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.0.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<T: Clock> fmt::Debug for StateView<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "StateView({})", self.0)
+    }
+}
+
+impl<T: Clock> fmt::Display for StateView<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Observer of the checker's internal steps, passed to
+/// `Linearizer::feed_span_with()`. All hooks do nothing by default; `()` is
+/// the no-op observer used by `Linearizer::feed_span()`.
+#[allow(unused_variables)]
+pub trait CheckerObserver<T: Clock = Timestamp> {
+    /// A span is fed in from given node.
+    fn on_feed(&mut self, node: Node, span: &OpSpan<T>) {}
+
+    /// State `child` is created from `parent` by applying `op` from `node`.
+    fn on_created(
+        &mut self,
+        parent: StateView<T>,
+        node: Node,
+        op: &OpSpan<T>,
+        child: StateView<T>,
+    ) {
+    }
+
+    /// State `parent` fails to apply `op` from `node` for given reason.
+    fn on_pruned(&mut self, parent: StateView<T>, node: Node, op: &OpSpan<T>, reason: PruneReason) {
+    }
+
+    /// A newly created state is merged into an existing equal `state`.
+    fn on_deduplicated(&mut self, state: StateView<T>) {}
+
+    /// State `state` cannot step further and is dropped.
+    fn on_dead_end(&mut self, state: StateView<T>) {}
+
+    /// Ops are settled, having become the common prefix of all states.
+    fn on_settled(&mut self, ops: &[(Node, OpSpan<T>)]) {}
+}
+
+impl<T: Clock> CheckerObserver<T> for () {}
@@ -2,6 +2,8 @@
 
 use std::fmt;
 
+use crate::Clock;
+
 /// Value type.
 pub type Value = u64;
 
@@ -9,22 +11,25 @@ pub type Value = u64;
 /// Currently assumes node IDs start from 0 (so can be used directly as index).
 pub type Node = usize;
 
-/// Timestamp type; expected to be **monotonically increasing** and **always
-/// unique**.
+/// Physical timestamp type, the default `Clock` of operation spans; expected
+/// to be **monotonically increasing** and **always unique**.
 pub type Timestamp = u64;
 
 /// Operation inputs enum.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OpInputs {
     Put { val: Value },
     Get,
-    Fail,    // leaves value uncertain
-    Stopped, // indicates node temporarily stopped
-    Resumed, // indicates node execution resumed
+    Cas { old: Value, new: Value }, // compare-and-swap
+    Fail,                           // leaves value uncertain
+    Stopped,                        // indicates node temporarily stopped
+    Resumed,                        // indicates node execution resumed
 }
 
 /// Operation result enum.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OpResult {
     Put,
     Get {
@@ -33,19 +38,21 @@ pub enum OpResult {
     Dummy,
 }
 
-/// An operation span with start-end timestamps.
+/// An operation span with start-end timestamps, generic over the type of
+/// timestamps (physical `Timestamp` by default).
 #[derive(Clone)]
-pub struct OpSpan {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpSpan<T: Clock = Timestamp> {
     pub(crate) inputs: OpInputs,
     pub(crate) result: OpResult,
-    pub(crate) ts_req: Timestamp,
-    pub(crate) ts_ack: Timestamp,
+    pub(crate) ts_req: T,
+    pub(crate) ts_ack: T,
 }
 
-impl OpSpan {
+impl<T: Clock> OpSpan<T> {
     /// Create an `OpSpan` for a successful Put operation.
-    pub fn put(val_i: Value, ts_req: Timestamp, ts_ack: Timestamp) -> Self {
-        assert!(ts_ack > ts_req);
+    pub fn put(val_i: Value, ts_req: T, ts_ack: T) -> Self {
+        assert!(ts_req.happens_before(&ts_ack));
         OpSpan {
             inputs: OpInputs::Put { val: val_i },
             result: OpResult::Put,
@@ -55,8 +62,8 @@ impl OpSpan {
     }
 
     /// Create an `OpSpan` for a successful Get operation.
-    pub fn get(val_o: Option<Value>, ts_req: Timestamp, ts_ack: Timestamp) -> Self {
-        assert!(ts_ack > ts_req);
+    pub fn get(val_o: Option<Value>, ts_req: T, ts_ack: T) -> Self {
+        assert!(ts_req.happens_before(&ts_ack));
         OpSpan {
             inputs: OpInputs::Get,
             result: OpResult::Get { val: val_o },
@@ -65,9 +72,21 @@ impl OpSpan {
         }
     }
 
+    /// Create an `OpSpan` for a successful compare-and-swap operation, which
+    /// atomically found value `old` and replaced it with `new`.
+    pub fn cas(old: Value, new: Value, ts_req: T, ts_ack: T) -> Self {
+        assert!(ts_req.happens_before(&ts_ack));
+        OpSpan {
+            inputs: OpInputs::Cas { old, new },
+            result: OpResult::Put,
+            ts_req,
+            ts_ack,
+        }
+    }
+
     /// Create an `OpSpan` for a failed operation, leaving value uncertain.
-    pub fn fail(ts_req: Timestamp, ts_ack: Timestamp) -> Self {
-        assert!(ts_ack > ts_req);
+    pub fn fail(ts_req: T, ts_ack: T) -> Self {
+        assert!(ts_req.happens_before(&ts_ack));
         OpSpan {
             inputs: OpInputs::Fail,
             result: OpResult::Dummy,
@@ -78,22 +97,22 @@ impl OpSpan {
 
     /// Special constructor for an `OpSpan` that indicates stopping of a
     /// node's execution.
-    pub fn stopped(ts: Timestamp) -> Self {
+    pub fn stopped(ts: T) -> Self {
         OpSpan {
             inputs: OpInputs::Stopped,
             result: OpResult::Dummy,
-            ts_req: ts,
+            ts_req: ts.clone(),
             ts_ack: ts,
         }
     }
 
     /// Special constructor for an `OpSpan` that indicates resuming of a
     /// node's execution.
-    pub fn resumed(ts: Timestamp) -> Self {
+    pub fn resumed(ts: T) -> Self {
         OpSpan {
             inputs: OpInputs::Resumed,
             result: OpResult::Dummy,
-            ts_req: ts,
+            ts_req: ts.clone(),
             ts_ack: ts,
         }
     }
@@ -102,18 +121,18 @@ impl OpSpan {
     pub fn is_normal(&self) -> bool {
         matches!(
             self.inputs,
-            OpInputs::Put { .. } | OpInputs::Get | OpInputs::Fail
+            OpInputs::Put { .. } | OpInputs::Get | OpInputs::Cas { .. } | OpInputs::Fail
         )
     }
 }
 
-impl fmt::Debug for OpSpan {
+impl<T: Clock> fmt::Debug for OpSpan<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}<{}>-<{}>", self, self.ts_req, self.ts_ack,)
     }
 }
 
-impl fmt::Display for OpSpan {
+impl<T: Clock> fmt::Display for OpSpan<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
@@ -121,6 +140,7 @@ impl fmt::Display for OpSpan {
             match self.inputs {
                 OpInputs::Put { val } => format!("Put({})", val),
                 OpInputs::Get => "Get".into(),
+                OpInputs::Cas { old, new } => format!("Cas({},{})", old, new),
                 OpInputs::Fail => "Fail".into(),
                 OpInputs::Stopped => "Stopped".into(),
                 OpInputs::Resumed => "Resumed".into(),
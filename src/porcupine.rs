@@ -0,0 +1,188 @@
+//! Importer of operation logs of Go's Porcupine checker, enabled by the
+//! `serde` feature. Input is either a JSON array of operations or one
+//! operation per line, each as Porcupine's `Operation` struct:
+//!
+//! ```text
+//! {"ClientId":0,"Input":{"Op":"put","Key":"x","Value":"a"},"Call":10,"Output":null,"Return":25}
+//! {"ClientId":1,"Input":{"Op":"get","Key":"x"},"Call":12,"Output":{"Value":"a"},"Return":20}
+//! ```
+//!
+//! Conversion rules:
+//!
+//! - `Input.Op` is `get`/`read` (or `0`, or `true`) for a Get, `put`/`write`
+//!   (or `1`, or `false`) for a Put, matching Porcupine's example KV and
+//!   register models; field names are case-insensitive
+//! - ops are partitioned by `Input.Key` (absent for a single register) into
+//!   one history per key, with clients numbered as nodes from 1 in order of
+//!   first op on that key
+//! - values, given as `Input.Value` and `Output.Value` (or `Output` itself),
+//!   are interned per key into IDs, with `0`, `""` and `null` all standing
+//!   for the initial value of Porcupine's models
+//! - each history starts with node 0 writing the initial value and
+//!   stopping, before all other ops
+//! - `Call` and `Return` times are replaced by their ranks among all events,
+//!   with calls ordered before returns at equal times as Porcupine does
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+
+use serde_json::Value as Json;
+
+use crate::{History, HistoryError, Node, OpSpan, Timestamp, Value};
+
+/// Value ID standing for the initial value of every key.
+const INITIAL_VALUE: Value = 0;
+
+/// Timestamp at which node 0 stops after writing the initial value; ops are
+/// timestamped after it.
+const INITIAL_END: Timestamp = 3;
+
+/// One parsed Porcupine operation.
+#[derive(Debug)]
+struct PorcupineOp {
+    client: i64,
+    key: String,
+    is_get: bool,
+    value: Json,
+    call: i64,
+    ret: i64,
+}
+
+/// Get a field of a JSON object, ignoring case of its name.
+fn field<'a>(json: &'a Json, name: &str) -> Option<&'a Json> {
+    json.as_object()?
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v)
+}
+
+/// Parse one operation from its JSON object.
+fn parse_op(json: &Json) -> Result<PorcupineOp, String> {
+    let int = |name: &str| {
+        field(json, name)
+            .and_then(Json::as_i64)
+            .ok_or(format!("missing or invalid {}", name))
+    };
+    let input = field(json, "Input").ok_or("missing Input")?;
+    let is_get = match field(input, "Op").ok_or("missing Input.Op")? {
+        Json::String(op) => match op.to_ascii_lowercase().as_str() {
+            "get" | "read" => true,
+            "put" | "write" => false,
+            _ => return Err(format!("unsupported op '{}'", op)),
+        },
+        Json::Number(op) if op.as_u64() == Some(0) => true,
+        Json::Number(op) if op.as_u64() == Some(1) => false,
+        Json::Bool(op) => *op,
+        op => return Err(format!("unsupported op {}", op)),
+    };
+    let key = match field(input, "Key") {
+        None | Some(Json::Null) => String::new(),
+        Some(Json::String(key)) => key.clone(),
+        Some(key) => key.to_string(),
+    };
+    let value = if is_get {
+        match field(json, "Output") {
+            Some(output) if output.is_object() => field(output, "Value").cloned(),
+            output => output.cloned(),
+        }
+    } else {
+        field(input, "Value").cloned()
+    };
+    Ok(PorcupineOp {
+        client: int("ClientId")?,
+        key,
+        is_get,
+        value: value.unwrap_or(Json::Null),
+        call: int("Call")?,
+        ret: int("Return")?,
+    })
+}
+
+impl History {
+    /// Read a Porcupine operation log, converting it as described in the
+    /// module docs. Returns one history per key, keyed by the key (empty for
+    /// a single register).
+    pub fn read_porcupine(reader: impl BufRead) -> Result<BTreeMap<String, Self>, HistoryError> {
+        let mut ops = vec![];
+        let stream = serde_json::Deserializer::from_reader(reader).into_iter::<Json>();
+        for json in stream {
+            let json = json.map_err(|e| HistoryError::Parse {
+                line: e.line(),
+                msg: e.to_string(),
+            })?;
+            match json {
+                Json::Array(array) => ops.extend(array),
+                json => ops.push(json),
+            }
+        }
+        let ops = ops
+            .iter()
+            .enumerate()
+            .map(|(i, json)| {
+                parse_op(json)
+                    .map_err(|msg| HistoryError::Invalid(format!("operation {}: {}", i + 1, msg)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // rank call and return times into unique timestamps, calls first
+        let mut events: Vec<(i64, bool, usize)> = ops
+            .iter()
+            .enumerate()
+            .flat_map(|(i, op)| [(op.call, false, i), (op.ret, true, i)])
+            .collect();
+        events.sort();
+        let mut ts_req = vec![0; ops.len()];
+        let mut ts_ack = vec![0; ops.len()];
+        for (rank, &(_, is_return, i)) in events.iter().enumerate() {
+            let ts = rank as Timestamp + INITIAL_END + 1;
+            if is_return {
+                ts_ack[i] = ts;
+            } else {
+                ts_req[i] = ts;
+            }
+        }
+
+        // feed ops of each key in order of return
+        let mut histories: BTreeMap<String, Self> = BTreeMap::new();
+        let mut nodes: HashMap<(&str, i64), Node> = HashMap::new();
+        let mut values: HashMap<(&str, String), Value> = HashMap::new();
+        for &(_, is_return, i) in &events {
+            if !is_return {
+                continue;
+            }
+            let op = &ops[i];
+            let history = histories.entry(op.key.clone()).or_insert_with(|| {
+                let mut history = History::new();
+                history
+                    .push(0, OpSpan::put(INITIAL_VALUE, 1, INITIAL_END - 1))
+                    .unwrap();
+                history.push(0, OpSpan::stopped(INITIAL_END)).unwrap();
+                history
+            });
+            let num_nodes = history.num_nodes();
+            let node = *nodes.entry((&op.key, op.client)).or_insert(num_nodes);
+
+            let val = match &op.value {
+                Json::Null => INITIAL_VALUE,
+                Json::String(s) if s.is_empty() => INITIAL_VALUE,
+                Json::Number(n) if n.as_f64() == Some(0.0) => INITIAL_VALUE,
+                val => {
+                    let num_values = values.len() as Value;
+                    *values
+                        .entry((&op.key, val.to_string()))
+                        .or_insert(INITIAL_VALUE + 1 + num_values)
+                }
+            };
+            let span = if op.is_get {
+                OpSpan::get(Some(val), ts_req[i], ts_ack[i])
+            } else {
+                OpSpan::put(val, ts_req[i], ts_ack[i])
+            };
+            history
+                .push(node, span)
+                .map_err(|e| HistoryError::Invalid(format!("operation {}: {}", i + 1, e)))?;
+        }
+
+        Ok(histories)
+    }
+}
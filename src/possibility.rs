@@ -5,8 +5,12 @@ use std::cmp;
 use std::collections::{HashSet, VecDeque};
 use std::fmt;
 use std::hash;
+use std::mem;
 
-use crate::{Node, OpInputs, OpResult, OpSpan, Value};
+use crate::{
+    CheckerObserver, Clock, ConsistencyModel, Node, OpInputs, OpResult, OpSpan, PruneReason,
+    StateView, Value,
+};
 
 /// A possible state linearized upto the current point.
 ///
@@ -15,38 +19,53 @@ use crate::{Node, OpInputs, OpResult, OpSpan, Value};
 ///       store indices/pointers to which op in each node's queue is its next
 ///       head; the lineage history, if needed, can be stored similarly
 #[derive(Debug, Clone)]
-pub(crate) struct Possibility {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Possibility<T: Clock> {
     /// Current object value. Cases:
     ///   - `None`: value uncertain, anything matches
     ///   - `Some(None)`: value is nil
     ///   - `Some(val)`: value is non-nil `val`
+    #[cfg_attr(feature = "serde", serde(with = "serde_val"))]
     current_val: Option<Option<Value>>,
 
     /// Linear history of operations applied that led to `current_val`.
-    lineage_history: Vec<(Node, OpSpan)>,
+    lineage_history: Vec<(Node, OpSpan<T>)>,
 
     /// Node-indexed queues of operations to be checked from that node.
-    queued_spans: Vec<VecDeque<OpSpan>>,
+    queued_spans: Vec<VecDeque<OpSpan<T>>>,
+
+    /// Recently overwritten values (oldest first), each paired with the
+    /// finish timestamp of the op that overwrote it. Only tracked under
+    /// models that allow stale Gets.
+    #[cfg_attr(feature = "serde", serde(with = "serde_val::pairs"))]
+    stale_vals: VecDeque<(Option<Option<Value>>, T)>,
+
+    /// Applied writes that may still overlap with future Gets, each with its
+    /// written value (`None` if unknown, i.e., a failed op) and timestamps.
+    /// Only tracked under regular and safe register semantics.
+    recent_writes: VecDeque<(Option<Value>, T, T)>,
 }
 
-impl Possibility {
+impl<T: Clock> Possibility<T> {
     /// Make an initial empty state with null value.
     pub(crate) fn initial(num_nodes: usize) -> Self {
         Possibility {
             current_val: Some(None),
             lineage_history: vec![],
             queued_spans: (0..num_nodes).map(|_| VecDeque::new()).collect(),
+            stale_vals: VecDeque::new(),
+            recent_writes: VecDeque::new(),
         }
     }
 
     /// Add a new span to its corresponding queue.
-    pub(crate) fn append_span(&mut self, node: Node, span: OpSpan) {
+    pub(crate) fn append_span(&mut self, node: Node, span: OpSpan<T>) {
         debug_assert!(node < self.queued_spans.len());
-        debug_assert!((span.ts_ack > span.ts_req) || (!span.is_normal()));
+        debug_assert!(span.ts_req.happens_before(&span.ts_ack) || (!span.is_normal()));
         if let Some(tail) = self.queued_spans[node].back() {
             // for every node, its submitted operations must naturally follow
             // a sequential order already
-            assert!(span.ts_req > tail.ts_ack);
+            assert!(tail.ts_ack.happens_before(&span.ts_req));
             if matches!(span.inputs, OpInputs::Resumed) {
                 assert!(matches!(tail.inputs, OpInputs::Stopped));
             } else {
@@ -71,31 +90,104 @@ impl Possibility {
                 .all(|q| !q.front().unwrap().is_normal()))
     }
 
-    /// Consume myself and step into 0-to-some further possible state(s). The
-    /// resulting states might still be steppable.
-    pub(crate) fn step(self) -> HashSet<Self> {
-        debug_assert!(self.can_step());
-        let min_ts_ack = self
-            .queued_spans
+    /// Get the number of nodes, i.e., of per-node queues.
+    pub(crate) fn num_nodes(&self) -> usize {
+        self.queued_spans.len()
+    }
+
+    /// Get the approximate memory taken by this state, in bytes.
+    pub(crate) fn approx_size(&self) -> usize {
+        let span_size = mem::size_of::<(Node, OpSpan<T>)>();
+        mem::size_of::<Self>()
+            + self.lineage_history.len() * span_size
+            + self.queued_spans.iter().map(|q| q.len()).sum::<usize>() * span_size
+            + self.queued_spans.len() * mem::size_of::<VecDeque<OpSpan<T>>>()
+            + self.stale_vals.len() * mem::size_of::<(Option<Option<Value>>, T)>()
+            + self.recent_writes.len() * mem::size_of::<(Option<Value>, T, T)>()
+    }
+
+    /// Get the linear history of operations applied.
+    pub(crate) fn lineage(&self) -> &[(Node, OpSpan<T>)] {
+        &self.lineage_history
+    }
+
+    /// Get the current object value.
+    pub(crate) fn current_val(&self) -> Option<Option<Value>> {
+        self.current_val
+    }
+
+    /// Get the number of operations queued from given node.
+    pub(crate) fn queue_len(&self, node: Node) -> usize {
+        self.queued_spans[node].len()
+    }
+
+    /// Remove and return the first `len` operations of the lineage history,
+    /// once they are settled across all possibilities.
+    pub(crate) fn settle(&mut self, len: usize) -> Vec<(Node, OpSpan<T>)> {
+        self.lineage_history.drain(..len).collect()
+    }
+
+    /// Get all operations not yet applied, node by node in queue order.
+    pub(crate) fn queued(&self) -> impl Iterator<Item = (Node, &OpSpan<T>)> {
+        self.queued_spans
             .iter()
-            .filter_map(|q| {
-                let head = q.front().unwrap();
-                if head.is_normal() {
-                    Some(head.ts_ack)
-                } else {
-                    None
-                }
-            })
-            .min()
-            .unwrap();
+            .enumerate()
+            .flat_map(|(node, q)| q.iter().map(move |span| (node as Node, span)))
+    }
+
+    /// Step into 0-to-some further possible state(s). The resulting states
+    /// might still be steppable.
+    ///
+    /// Under a model without the real-time constraint (e.g., sequential
+    /// consistency), any normal head op is a possible candidate; per-node
+    /// program order is still preserved by the queues themselves.
+    ///
+    /// Given a `max_clock_skew` between nodes, a head op is ordered after
+    /// another one only if it started at least that much after the other
+    /// one finished.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, fields(state = %self))
+    )]
+    pub(crate) fn step(
+        &mut self,
+        model: ConsistencyModel,
+        max_clock_skew: u64,
+        observer: &mut impl CheckerObserver<T>,
+    ) -> HashSet<Self> {
+        debug_assert!(self.can_step());
+        if model.is_stale() || model.is_overlap_tolerant() {
+            self.forget_recent(model, max_clock_skew);
+        }
 
         let mut new_states = HashSet::new();
         for (node, q) in self.queued_spans.iter().enumerate() {
             let head = q.front().unwrap();
-            if head.is_normal() && head.ts_req < min_ts_ack {
+            if head.is_normal()
+                && (!model.is_real_time()
+                    || !self.queued_spans.iter().any(|q| {
+                        // no other head op finished definitely before it
+                        let other = q.front().unwrap();
+                        other.is_normal() && other.ts_ack.precedes(&head.ts_req, max_clock_skew)
+                    }))
+            {
                 // possible candidate as the next op
-                if let Some(new_state) = self.apply_head(node as Node) {
-                    new_states.insert(new_state);
+                match self.apply_head(node as Node, model, max_clock_skew) {
+                    Ok(new_state) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::trace!(node, op = %head, child = %new_state, "applied");
+                        observer.on_created(StateView(self), node, head, StateView(&new_state));
+                        if let Some(old_state) = new_states.replace(new_state) {
+                            #[cfg(feature = "tracing")]
+                            tracing::trace!(state = %old_state, "deduplicated");
+                            observer.on_deduplicated(StateView(&old_state));
+                        }
+                    }
+                    Err(reason) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::trace!(node, op = %head, %reason, "pruned");
+                        observer.on_pruned(StateView(self), node, head, reason);
+                    }
                 }
             }
         }
@@ -103,10 +195,41 @@ impl Possibility {
         new_states
     }
 
+    /// Forget remembered stale values and recent writes that can no longer
+    /// affect any future Get.
+    fn forget_recent(&mut self, model: ConsistencyModel, max_clock_skew: u64) {
+        // any future op starts no earlier than its node's current head
+        let heads: Vec<&OpSpan<T>> = self
+            .queued_spans
+            .iter()
+            .map(|q| q.front().unwrap())
+            .collect();
+        if let ConsistencyModel::DeltaAtomic { delta } = model {
+            self.stale_vals.retain(|(_, ts)| {
+                let ts = ts.shifted(delta);
+                !heads.iter().all(|head| ts.happens_before(&head.ts_req))
+            });
+        }
+        self.recent_writes.retain(|(_, _, ts_ack)| {
+            !heads
+                .iter()
+                .all(|head| ts_ack.precedes(&head.ts_req, max_clock_skew))
+        });
+    }
+
     /// Attempt to apply the head operation on given node's queue as the next
-    /// operation, returning a valid copy of state on success or a `None` on
-    /// error or value mismatch.
-    fn apply_head(&self, node: Node) -> Option<Self> {
+    /// operation, returning a valid copy of state on success or the reason
+    /// of failure on error or value mismatch.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(self, model))
+    )]
+    fn apply_head(
+        &self,
+        node: Node,
+        model: ConsistencyModel,
+        max_clock_skew: u64,
+    ) -> Result<Self, PruneReason> {
         let op = self.queued_spans[node].front().unwrap();
         match op.inputs {
             OpInputs::Put { val } => {
@@ -117,29 +240,53 @@ impl Possibility {
                         new_state
                             .lineage_history
                             .push((node, new_state.queued_spans[node].pop_front().unwrap()));
-                        new_state.current_val = Some(Some(val));
-                        Some(new_state)
+                        new_state.overwrite(Some(Some(val)), op, model);
+                        Ok(new_state)
                     }
-                    _ => None,
+                    _ => Err(PruneReason::InvalidResult),
                 }
             }
 
             OpInputs::Get => {
                 match op.result {
                     OpResult::Get { val } => {
-                        if self.current_val.is_none() || self.current_val.unwrap() == val {
+                        if self.current_val.is_none()
+                            || self.current_val.unwrap() == val
+                            || self.stale_matches(val, &op.ts_req, model)
+                            || self.overlap_matches(val, op, model, max_clock_skew)
+                        {
                             // successful Get with matching value
                             let mut new_state = self.clone();
                             new_state
                                 .lineage_history
                                 .push((node, new_state.queued_spans[node].pop_front().unwrap()));
-                            Some(new_state)
+                            Ok(new_state)
                         } else {
                             // successful Get but values mismatch
-                            None
+                            Err(PruneReason::GetMismatch)
+                        }
+                    }
+                    _ => Err(PruneReason::InvalidResult),
+                }
+            }
+
+            OpInputs::Cas { old, new } => {
+                match op.result {
+                    OpResult::Put => {
+                        // successful Cas must have observed the latest value,
+                        // even under models allowing stale Gets
+                        if self.current_val.is_none() || self.current_val.unwrap() == Some(old) {
+                            let mut new_state = self.clone();
+                            new_state
+                                .lineage_history
+                                .push((node, new_state.queued_spans[node].pop_front().unwrap()));
+                            new_state.overwrite(Some(Some(new)), op, model);
+                            Ok(new_state)
+                        } else {
+                            Err(PruneReason::CasMismatch)
                         }
                     }
-                    _ => None,
+                    _ => Err(PruneReason::InvalidResult),
                 }
             }
 
@@ -149,8 +296,8 @@ impl Possibility {
                 new_state
                     .lineage_history
                     .push((node, new_state.queued_spans[node].pop_front().unwrap()));
-                new_state.current_val = None;
-                Some(new_state)
+                new_state.overwrite(None, op, model);
+                Ok(new_state)
             }
 
             _ => {
@@ -158,9 +305,87 @@ impl Possibility {
             }
         }
     }
+
+    /// Check if a Get that returned `val` could be justified by a write
+    /// overlapping with it, either applied already or still queued.
+    fn overlap_matches(
+        &self,
+        val: Option<Value>,
+        get: &OpSpan<T>,
+        model: ConsistencyModel,
+        max_clock_skew: u64,
+    ) -> bool {
+        if !model.is_overlap_tolerant() {
+            return false;
+        }
+        let justifies = |written: Option<Value>| {
+            matches!(model, ConsistencyModel::Safe) || written.is_none() || written == val
+        };
+
+        // with clock skew, spans close enough in time are also overlapping
+        let overlaps = |ts_req: &T, ts_ack: &T| {
+            !get.ts_ack.precedes(ts_req, max_clock_skew)
+                && !ts_ack.precedes(&get.ts_req, max_clock_skew)
+        };
+        if self
+            .recent_writes
+            .iter()
+            .any(|(written, ts_req, ts_ack)| overlaps(ts_req, ts_ack) && justifies(*written))
+        {
+            return true;
+        }
+        self.queued_spans.iter().any(|q| {
+            q.iter()
+                .take_while(|op| !get.ts_ack.precedes(&op.ts_req, max_clock_skew))
+                .any(|op| {
+                    overlaps(&op.ts_req, &op.ts_ack)
+                        && match op.inputs {
+                            OpInputs::Put { val } | OpInputs::Cas { new: val, .. } => {
+                                justifies(Some(val))
+                            }
+                            OpInputs::Fail => justifies(None),
+                            _ => false,
+                        }
+                })
+        })
+    }
+
+    /// Overwrite the current value by the given write op, remembering the
+    /// old value if stale Gets are allowed, or the write itself if Gets
+    /// overlapping with it are tolerated.
+    fn overwrite(&mut self, val: Option<Option<Value>>, op: &OpSpan<T>, model: ConsistencyModel) {
+        if model.is_overlap_tolerant() {
+            self.recent_writes
+                .push_back((val.flatten(), op.ts_req.clone(), op.ts_ack.clone()));
+        }
+        if model.is_stale() {
+            self.stale_vals
+                .push_back((self.current_val, op.ts_ack.clone()));
+            if let ConsistencyModel::KAtomic { k } = model {
+                while self.stale_vals.len() >= k {
+                    self.stale_vals.pop_front();
+                }
+            }
+        }
+        self.current_val = val;
+    }
+
+    /// Check if a Get that started at `ts_req` and returned `val` could have
+    /// observed one of the remembered stale values.
+    fn stale_matches(&self, val: Option<Value>, ts_req: &T, model: ConsistencyModel) -> bool {
+        self.stale_vals.iter().any(|(stale_val, ts)| {
+            (stale_val.is_none() || stale_val.unwrap() == val)
+                && match model {
+                    ConsistencyModel::DeltaAtomic { delta } => {
+                        !ts.shifted(delta).happens_before(ts_req)
+                    }
+                    _ => true,
+                }
+        })
+    }
 }
 
-impl fmt::Display for Possibility {
+impl<T: Clock> fmt::Display for Possibility<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
@@ -188,7 +413,7 @@ impl fmt::Display for Possibility {
     }
 }
 
-impl cmp::PartialEq for Possibility {
+impl<T: Clock> cmp::PartialEq for Possibility<T> {
     fn eq(&self, other: &Self) -> bool {
         self.current_val == other.current_val
             && self.queued_spans.len() == other.queued_spans.len()
@@ -200,17 +425,96 @@ impl cmp::PartialEq for Possibility {
                 // comparing length of each node's queue is enough when
                 // determining equality between possibilities
                 .all(|(ls, lo)| ls == lo)
+            && self.stale_vals == other.stale_vals
+            && self.recent_writes == other.recent_writes
     }
 }
 
-impl cmp::Eq for Possibility {}
+impl<T: Clock> cmp::Eq for Possibility<T> {}
 
-impl hash::Hash for Possibility {
+impl<T: Clock> hash::Hash for Possibility<T> {
     fn hash<H: hash::Hasher>(&self, state: &mut H) {
         self.current_val.hash(state);
         self.queued_spans.len().hash(state);
         for q in &self.queued_spans {
             q.len().hash(state);
         }
+        self.stale_vals.hash(state);
+        self.recent_writes.hash(state);
+    }
+}
+
+/// Serde helpers for possibly uncertain values, as serializing
+/// `Option<Option<Value>>` directly would lose the nil `Some(None)`.
+#[cfg(feature = "serde")]
+mod serde_val {
+    use std::collections::VecDeque;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::Value;
+
+    #[derive(Serialize, Deserialize)]
+    enum Uncertain {
+        Unknown,
+        Nil,
+        Val(Value),
+    }
+
+    impl From<Option<Option<Value>>> for Uncertain {
+        fn from(val: Option<Option<Value>>) -> Self {
+            match val {
+                None => Uncertain::Unknown,
+                Some(None) => Uncertain::Nil,
+                Some(Some(val)) => Uncertain::Val(val),
+            }
+        }
+    }
+
+    impl From<Uncertain> for Option<Option<Value>> {
+        fn from(val: Uncertain) -> Self {
+            match val {
+                Uncertain::Unknown => None,
+                Uncertain::Nil => Some(None),
+                Uncertain::Val(val) => Some(Some(val)),
+            }
+        }
+    }
+
+    pub(super) fn serialize<S: Serializer>(
+        val: &Option<Option<Value>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Uncertain::from(*val).serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Option<Value>>, D::Error> {
+        Uncertain::deserialize(deserializer).map(Into::into)
+    }
+
+    /// Same for a queue of values each paired with a timestamp.
+    pub(super) mod pairs {
+        use super::*;
+
+        type Pairs<T> = VecDeque<(Option<Option<Value>>, T)>;
+
+        pub(crate) fn serialize<T: Serialize, S: Serializer>(
+            pairs: &Pairs<T>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(pairs.iter().map(|(val, ts)| (Uncertain::from(*val), ts)))
+        }
+
+        pub(crate) fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Pairs<T>, D::Error> {
+            let pairs: Vec<(Uncertain, T)> = Vec::deserialize(deserializer)?;
+            Ok(pairs
+                .into_iter()
+                .map(|(val, ts)| (val.into(), ts))
+                .collect())
+        }
     }
 }
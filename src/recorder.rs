@@ -0,0 +1,220 @@
+//! Thread-safe recorder that automatically timestamps operations issued by
+//! in-process concurrent threads and feeds them into a linearizer.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::Instant;
+
+use crate::{Linearizer, Node, OpInputs, OpSpan, Timestamp, Value};
+
+/// Source of timestamps of a `Recorder`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecorderClock {
+    /// A shared counter incremented on every stamp.
+    #[default]
+    Counter,
+
+    /// Nanoseconds elapsed since the recorder's creation, bumped when needed
+    /// to keep timestamps unique.
+    Instant,
+}
+
+/// State protected by the recorder's lock.
+#[derive(Debug)]
+struct RecorderState {
+    /// The linearizer being fed.
+    linearizer: Linearizer,
+
+    /// Verdict of the last feed.
+    ok: bool,
+
+    /// Map from thread ID to its assigned node ID.
+    thread_nodes: HashMap<ThreadId, Node>,
+
+    /// Node-indexed flags of whether the node is currently stopped.
+    stopped: Vec<bool>,
+}
+
+/// State shared by all handles of a recorder.
+#[derive(Debug)]
+struct RecorderShared {
+    /// Source of timestamps.
+    clock: RecorderClock,
+
+    /// Time of creation, for `RecorderClock::Instant`.
+    start: Instant,
+
+    /// Last timestamp handed out.
+    last_ts: AtomicU64,
+
+    /// Lock-protected state.
+    state: Mutex<RecorderState>,
+}
+
+/// Thread-safe recorder handle, cheap to clone. Every thread that records
+/// operations through it gets assigned its own node ID on its first
+/// recorded operation.
+///
+/// Invocation and completion of each operation are stamped from a shared
+/// monotonic clock, and the resulting span is fed into the linearizer upon
+/// completion. Once a thread is done, it should call `.stop()` so that the
+/// linearizer does not wait on its future operations.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    shared: Arc<RecorderShared>,
+}
+
+impl Recorder {
+    /// Create a new recorder feeding into the given fresh linearizer, with a
+    /// shared counter as its clock.
+    pub fn new(linearizer: Linearizer) -> Self {
+        Self::with_clock(linearizer, RecorderClock::default())
+    }
+
+    /// Create a new recorder feeding into the given fresh linearizer, with
+    /// the given source of timestamps.
+    pub fn with_clock(linearizer: Linearizer, clock: RecorderClock) -> Self {
+        let num_nodes = linearizer.num_nodes;
+        Recorder {
+            shared: Arc::new(RecorderShared {
+                clock,
+                start: Instant::now(),
+                last_ts: AtomicU64::new(0),
+                state: Mutex::new(RecorderState {
+                    linearizer,
+                    ok: true,
+                    thread_nodes: HashMap::new(),
+                    stopped: vec![false; num_nodes],
+                }),
+            }),
+        }
+    }
+
+    /// Take a new unique timestamp, strictly larger than all previous ones.
+    pub fn now(&self) -> Timestamp {
+        let last_ts = &self.shared.last_ts;
+        match self.shared.clock {
+            RecorderClock::Counter => last_ts.fetch_add(1, Ordering::SeqCst) + 1,
+            RecorderClock::Instant => {
+                let elapsed = self.shared.start.elapsed().as_nanos() as Timestamp;
+                let prev = last_ts
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |ts| {
+                        Some(elapsed.max(ts + 1))
+                    })
+                    .unwrap();
+                elapsed.max(prev + 1)
+            }
+        }
+    }
+
+    /// Get the node ID of the calling thread, assigning a new one if this is
+    /// its first time.
+    pub fn node(&self) -> Node {
+        let mut state = self.shared.state.lock().unwrap();
+        Self::thread_node(&mut state)
+    }
+
+    /// Look up or assign the node ID of the calling thread.
+    fn thread_node(state: &mut RecorderState) -> Node {
+        let num_assigned = state.thread_nodes.len();
+        let node = *state
+            .thread_nodes
+            .entry(thread::current().id())
+            .or_insert(num_assigned);
+        assert!(
+            node < state.linearizer.num_nodes,
+            "more recording threads than nodes"
+        );
+        node
+    }
+
+    /// Feed a completed span of the calling thread into the linearizer.
+    fn record(&self, span: OpSpan) {
+        let mut state = self.shared.state.lock().unwrap();
+        let node = Self::thread_node(&mut state);
+        match span.inputs {
+            OpInputs::Stopped => state.stopped[node] = true,
+            OpInputs::Resumed => state.stopped[node] = false,
+            _ => assert!(!state.stopped[node], "recording on a stopped node"),
+        }
+        state.ok = state.linearizer.feed_span(node, span);
+    }
+
+    /// Record a Put of `val` performed by running `op`.
+    pub fn put<R>(&self, val: Value, op: impl FnOnce() -> R) -> R {
+        let ts_req = self.now();
+        let ret = op();
+        self.record(OpSpan::put(val, ts_req, self.now()));
+        ret
+    }
+
+    /// Record a Get performed by running `op`, which returns the value read.
+    pub fn get(&self, op: impl FnOnce() -> Option<Value>) -> Option<Value> {
+        let ts_req = self.now();
+        let val = op();
+        self.record(OpSpan::get(val, ts_req, self.now()));
+        val
+    }
+
+    /// Record a Put of `val` performed by running a fallible `op`; an error
+    /// is recorded as a failed op that leaves the value uncertain.
+    pub fn try_put<R, E>(&self, val: Value, op: impl FnOnce() -> Result<R, E>) -> Result<R, E> {
+        let ts_req = self.now();
+        let ret = op();
+        let ts_ack = self.now();
+        self.record(if ret.is_ok() {
+            OpSpan::put(val, ts_req, ts_ack)
+        } else {
+            OpSpan::fail(ts_req, ts_ack)
+        });
+        ret
+    }
+
+    /// Record a Get performed by running a fallible `op`; an error is
+    /// recorded as a failed op.
+    pub fn try_get<E>(
+        &self,
+        op: impl FnOnce() -> Result<Option<Value>, E>,
+    ) -> Result<Option<Value>, E> {
+        let ts_req = self.now();
+        let ret = op();
+        let ts_ack = self.now();
+        self.record(match ret {
+            Ok(val) => OpSpan::get(val, ts_req, ts_ack),
+            Err(_) => OpSpan::fail(ts_req, ts_ack),
+        });
+        ret
+    }
+
+    /// Record that the calling thread stopped issuing operations.
+    pub fn stop(&self) {
+        self.record(OpSpan::stopped(self.now()));
+    }
+
+    /// Record that the calling thread resumed issuing operations.
+    pub fn resume(&self) {
+        self.record(OpSpan::resumed(self.now()));
+    }
+
+    /// Check if the recorded operations are still linearizable so far.
+    pub fn is_ok(&self) -> bool {
+        self.shared.state.lock().unwrap().ok
+    }
+
+    /// Stop all nodes that are not stopped yet (including ones never
+    /// assigned to any thread), then return the final verdict.
+    pub fn finish(&self) -> bool {
+        let mut state = self.shared.state.lock().unwrap();
+        for node in 0..state.stopped.len() {
+            if !state.stopped[node] {
+                state.stopped[node] = true;
+                state.ok = state
+                    .linearizer
+                    .feed_span(node, OpSpan::stopped(self.now()));
+            }
+        }
+        state.ok
+    }
+}
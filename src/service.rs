@@ -0,0 +1,91 @@
+//! Asynchronous checker service for tokio-based clients: spans are sent
+//! through a channel and checked by a background task.
+
+use tokio::sync::{mpsc, watch};
+use tokio::task::{self, JoinHandle};
+
+use crate::{Clock, Linearizer, Node, OpSpan, Timestamp, Verdict};
+
+/// Sender half for feeding spans into a `CheckerService`, cheap to clone.
+pub type SpanSender<T = Timestamp> = mpsc::UnboundedSender<(Node, OpSpan<T>)>;
+
+/// Asynchronous front-end of a linearizer. Sending never blocks the caller;
+/// the potentially heavy stepping runs on a blocking-capable background
+/// task, and verdicts are exposed through a watch channel.
+///
+/// Requires a running tokio runtime; enabled by the `async` feature.
+#[derive(Debug)]
+pub struct CheckerService<T: Clock + Send + 'static = Timestamp> {
+    /// Sender for feeding spans.
+    sender: SpanSender<T>,
+
+    /// Receiver of verdicts, updated whenever the verdict changes.
+    verdict: watch::Receiver<bool>,
+
+    /// Background checking task, returning the linearizer on exit.
+    task: JoinHandle<Linearizer<T>>,
+}
+
+impl<T: Clock + Send + 'static> CheckerService<T> {
+    /// Spawn the background checking task for the given linearizer.
+    pub fn spawn(mut linearizer: Linearizer<T>) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<(Node, OpSpan<T>)>();
+        let (verdict_tx, verdict) = watch::channel(linearizer.verdict() != Verdict::Violated);
+
+        let task = task::spawn_blocking(move || {
+            while let Some((node, span)) = receiver.blocking_recv() {
+                let ok = linearizer.feed_span(node, span);
+                verdict_tx.send_if_modified(|verdict| {
+                    let changed = *verdict != ok;
+                    *verdict = ok;
+                    changed
+                });
+            }
+            linearizer
+        });
+
+        CheckerService {
+            sender,
+            verdict,
+            task,
+        }
+    }
+
+    /// Get a new sender for feeding spans. Spans of the same node must be
+    /// sent in order.
+    pub fn sender(&self) -> SpanSender<T> {
+        self.sender.clone()
+    }
+
+    /// Get a new receiver of verdicts: `true` while linearizability holds.
+    pub fn verdict(&self) -> watch::Receiver<bool> {
+        self.verdict.clone()
+    }
+
+    /// Wait until a violation is detected, returning true; returns false if
+    /// all spans sent have been checked without any violation. Like
+    /// `finish()`, this drops the service's own sender, so all other
+    /// senders obtained must be dropped for the passing case to return.
+    /// Returns an error if the checking task panicked.
+    pub async fn violation(self) -> Result<bool, task::JoinError> {
+        let CheckerService {
+            sender,
+            mut verdict,
+            task,
+        } = self;
+        drop(sender);
+        if verdict.wait_for(|ok| !ok).await.is_ok() {
+            return Ok(true);
+        }
+        // watch closed, so the task has exited: normally or by panicking
+        Ok(task.await?.verdict() == Verdict::Violated)
+    }
+
+    /// Wait until all spans sent have been checked, then return the
+    /// linearizer. All other senders obtained must have been dropped,
+    /// otherwise this waits forever.
+    pub async fn finish(self) -> Linearizer<T> {
+        drop(self.sender);
+        self.task.await.expect("checker task panicked")
+    }
+}
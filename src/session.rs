@@ -0,0 +1,338 @@
+//! On-line checker of per-node session guarantees, as weaker alternatives to
+//! linearizability.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use crate::history::check_follows;
+use crate::{Clock, HistoryError, Node, OpInputs, OpResult, OpSpan, Timestamp, Value};
+
+/// Session guarantee enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SessionGuarantee {
+    /// A Get observes the node's own latest Put or something newer.
+    ReadYourWrites,
+    /// Successive Gets of a node never observe older values.
+    MonotonicReads,
+    /// Puts of a node are never observed out of its program order.
+    MonotonicWrites,
+    /// A Put is ordered after the values its node has observed before it.
+    WritesFollowReads,
+}
+
+impl SessionGuarantee {
+    /// All session guarantees.
+    pub const ALL: [SessionGuarantee; 4] = [
+        SessionGuarantee::ReadYourWrites,
+        SessionGuarantee::MonotonicReads,
+        SessionGuarantee::MonotonicWrites,
+        SessionGuarantee::WritesFollowReads,
+    ];
+}
+
+/// Report of which session guarantees still hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionReport {
+    pub read_your_writes: bool,
+    pub monotonic_reads: bool,
+    pub monotonic_writes: bool,
+    pub writes_follow_reads: bool,
+
+    /// Number of Gets not yet checked because the Put they observed has not
+    /// been fed in yet.
+    pub pending_gets: usize,
+
+    /// Number of Gets that observed a value no Put fed wrote by the time
+    /// their node stopped; these are skipped.
+    pub unwritten_gets: usize,
+}
+
+impl SessionReport {
+    /// Check if the given guarantee still holds.
+    pub fn holds(&self, guarantee: SessionGuarantee) -> bool {
+        match guarantee {
+            SessionGuarantee::ReadYourWrites => self.read_your_writes,
+            SessionGuarantee::MonotonicReads => self.monotonic_reads,
+            SessionGuarantee::MonotonicWrites => self.monotonic_writes,
+            SessionGuarantee::WritesFollowReads => self.writes_follow_reads,
+        }
+    }
+}
+
+impl fmt::Display for SessionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RYW:{} MR:{} MW:{} WFR:{} pending:{} unwritten:{}",
+            self.read_your_writes,
+            self.monotonic_reads,
+            self.monotonic_writes,
+            self.writes_follow_reads,
+            self.pending_gets,
+            self.unwritten_gets
+        )
+    }
+}
+
+/// Information about a fed Put, looked up by its (unique) value.
+#[derive(Debug, Clone)]
+struct WriteInfo<T: Clock> {
+    /// Node that issued the Put.
+    node: Node,
+
+    /// Position of the Put among all Puts of its node.
+    seq: usize,
+
+    /// Timestamps of the Put.
+    ts_req: T,
+    ts_ack: T,
+
+    /// Value observed by the node's last Get before the Put, if any.
+    dep: Option<Option<Value>>,
+}
+
+/// Per-node session state.
+#[derive(Debug, Clone)]
+struct Session<T: Clock> {
+    /// Queue of operations to be checked from this node.
+    queued_spans: VecDeque<OpSpan<T>>,
+
+    /// Last span fed, for checking that the next one follows it.
+    last_fed: Option<OpSpan<T>>,
+
+    /// Value observed by the last Get fed (not necessarily checked yet).
+    fed_get_val: Option<Option<Value>>,
+
+    /// Number of Puts fed so far.
+    fed_puts: usize,
+
+    /// Latest own Put checked.
+    last_write: Option<Value>,
+
+    /// Latest value observed by a checked Get.
+    last_read: Option<Option<Value>>,
+
+    /// Writer node-indexed latest position of Puts observed.
+    seen_seqs: Vec<Option<usize>>,
+
+    /// Values the observed Puts depend on, which must not be observed again.
+    dep_floors: HashSet<Value>,
+}
+
+/// On-line checker of session guarantees: read-your-writes, monotonic
+/// reads, monotonic writes and writes-follow-reads.
+///
+/// Values of Puts are assumed to be **unique**, so that every Get can be
+/// traced back to the Put it observed. A Put is considered older than
+/// another one only if it definitely finished before that one started; the
+/// initial nil value is older than all Puts. Failed ops are ignored. A Get
+/// is checked once the Put it observed has been fed; when its node stops,
+/// Gets still waiting for a Put are counted as `unwritten_gets` instead.
+#[derive(Debug, Clone)]
+pub struct SessionChecker<T: Clock = Timestamp> {
+    /// Node-indexed session states.
+    sessions: Vec<Session<T>>,
+
+    /// Map from Put value to its information.
+    writes: HashMap<Value, WriteInfo<T>>,
+
+    /// Current report.
+    report: SessionReport,
+}
+
+impl<T: Clock> SessionChecker<T> {
+    /// Create a new session guarantees checker.
+    pub fn new(num_nodes: usize) -> Self {
+        assert_ne!(num_nodes, 0);
+        SessionChecker {
+            sessions: (0..num_nodes)
+                .map(|_| Session {
+                    queued_spans: VecDeque::new(),
+                    last_fed: None,
+                    fed_get_val: None,
+                    fed_puts: 0,
+                    last_write: None,
+                    last_read: None,
+                    seen_seqs: vec![None; num_nodes],
+                    dep_floors: HashSet::new(),
+                })
+                .collect(),
+            writes: HashMap::new(),
+            report: SessionReport {
+                read_your_writes: true,
+                monotonic_reads: true,
+                monotonic_writes: true,
+                writes_follow_reads: true,
+                pending_gets: 0,
+                unwritten_gets: 0,
+            },
+        }
+    }
+
+    /// Feed in a new operation span, checking all session guarantees on as
+    /// many queued ops as possible.
+    ///
+    /// Returns the report of which guarantees still hold, or an error if the
+    /// span does not follow the previous one of its node or writes a value
+    /// already written.
+    pub fn feed_span(
+        &mut self,
+        node: Node,
+        span: OpSpan<T>,
+    ) -> Result<SessionReport, HistoryError> {
+        let Some(session) = self.sessions.get_mut(node) else {
+            return Err(HistoryError::Invalid(format!(
+                "node {} out of range for {} nodes",
+                node,
+                self.sessions.len()
+            )));
+        };
+        check_follows(node, session.last_fed.as_ref(), &span)?;
+        match (&span.inputs, &span.result) {
+            (OpInputs::Put { val }, OpResult::Put)
+            | (OpInputs::Cas { new: val, .. }, OpResult::Put) => {
+                if self.writes.contains_key(val) {
+                    return Err(HistoryError::Invalid(format!(
+                        "{:?} on node {} writes duplicate value {}",
+                        span, node, val
+                    )));
+                }
+                self.writes.insert(
+                    *val,
+                    WriteInfo {
+                        node,
+                        seq: session.fed_puts,
+                        ts_req: span.ts_req.clone(),
+                        ts_ack: span.ts_ack.clone(),
+                        dep: session.fed_get_val,
+                    },
+                );
+                session.fed_puts += 1;
+            }
+            (OpInputs::Get, OpResult::Get { val }) => {
+                session.fed_get_val = Some(*val);
+            }
+            _ => {}
+        }
+        let stopped = matches!(span.inputs, OpInputs::Stopped);
+        session.last_fed = Some(span.clone());
+        session.queued_spans.push_back(span);
+
+        // newly known Puts may unblock queued Gets of any node, and a
+        // stopped node will not see any more Puts of its own
+        for other in 0..self.sessions.len() {
+            self.check_queued(other, stopped && other == node);
+        }
+        self.report.pending_gets = self
+            .sessions
+            .iter()
+            .map(|s| {
+                s.queued_spans
+                    .iter()
+                    .filter(|op| matches!(op.inputs, OpInputs::Get))
+                    .count()
+            })
+            .sum();
+
+        Ok(self.report)
+    }
+
+    /// Get the current report.
+    pub fn report(&self) -> SessionReport {
+        self.report
+    }
+
+    /// Check if value `a` (`None` meaning the initial nil) is definitely
+    /// older than value `b`.
+    fn older(&self, a: Option<Value>, b: Option<Value>) -> bool {
+        match (a, b) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(a), Some(b)) => self.writes[&a]
+                .ts_ack
+                .happens_before(&self.writes[&b].ts_req),
+        }
+    }
+
+    /// Check if the Put (and its dependency) observed by a Get is known.
+    fn traceable(&self, val: Option<Value>) -> bool {
+        match val {
+            None => true,
+            Some(val) => match self.writes.get(&val) {
+                Some(info) => match info.dep {
+                    Some(Some(dep)) => self.writes.contains_key(&dep),
+                    _ => true,
+                },
+                None => false,
+            },
+        }
+    }
+
+    /// Check queued ops of given node in order, until hitting a Get that is
+    /// not traceable yet, unless `stopped`, in which case such Gets are
+    /// counted as unwritten if their Put is unknown and checked without
+    /// their unknown dependency otherwise.
+    fn check_queued(&mut self, node: Node, stopped: bool) {
+        while let Some(op) = self.sessions[node].queued_spans.front() {
+            match (&op.inputs, &op.result) {
+                (OpInputs::Put { val }, _) | (OpInputs::Cas { new: val, .. }, _) => {
+                    self.sessions[node].last_write = Some(*val);
+                }
+
+                (OpInputs::Get, OpResult::Get { val }) => {
+                    let val = *val;
+                    if !self.traceable(val) {
+                        if !stopped {
+                            break;
+                        }
+                        if val.is_some_and(|v| !self.writes.contains_key(&v)) {
+                            self.report.unwritten_gets += 1;
+                            self.sessions[node].queued_spans.pop_front();
+                            continue;
+                        }
+                    }
+                    let session = &self.sessions[node];
+
+                    if self.older(val, session.last_write) {
+                        self.report.read_your_writes = false;
+                    }
+                    if let Some(last_read) = session.last_read {
+                        if self.older(val, last_read) {
+                            self.report.monotonic_reads = false;
+                        }
+                    }
+                    if session
+                        .dep_floors
+                        .iter()
+                        .any(|&f| val == Some(f) || self.older(val, Some(f)))
+                    {
+                        self.report.writes_follow_reads = false;
+                    }
+
+                    let mut dep_floor = None;
+                    if let Some(info) = val.map(|v| &self.writes[&v]) {
+                        if session.seen_seqs[info.node].is_some_and(|seq| seq > info.seq) {
+                            self.report.monotonic_writes = false;
+                        }
+                        if let Some(Some(dep)) = info.dep {
+                            dep_floor = Some(dep).filter(|dep| self.writes.contains_key(dep));
+                        }
+                    }
+
+                    let session = &mut self.sessions[node];
+                    session.last_read = Some(val);
+                    if let Some(info) = val.map(|v| &self.writes[&v]) {
+                        let seen = &mut session.seen_seqs[info.node];
+                        *seen = Some(seen.map_or(info.seq, |seq| seq.max(info.seq)));
+                    }
+                    if let Some(dep) = dep_floor {
+                        session.dep_floors.insert(dep);
+                    }
+                }
+
+                _ => {}
+            }
+            self.sessions[node].queued_spans.pop_front();
+        }
+    }
+}
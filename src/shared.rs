@@ -0,0 +1,191 @@
+//! Thread-safe linearizer handle that many threads can feed concurrently,
+//! with stepping done by a dedicated background worker.
+
+use std::mem;
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle, Thread};
+
+use crate::{Clock, Linearizer, Node, OpSpan, Timestamp, Verdict};
+
+/// Marker of the worker having died, stored as the processed count.
+const WORKER_DEAD: usize = usize::MAX;
+
+/// State shared between feeding threads and the worker.
+#[derive(Debug)]
+struct SharedState<T: Clock> {
+    /// Node-indexed shards of spans fed but not yet taken by the worker.
+    shards: Vec<Mutex<Vec<OpSpan<T>>>>,
+
+    /// Set when any shard has new spans since the worker last drained.
+    dirty: AtomicBool,
+
+    /// Set when the worker should exit after draining all shards.
+    shutdown: AtomicBool,
+
+    /// Verdict after the spans processed so far.
+    ok: AtomicBool,
+
+    /// Number of spans fed in so far.
+    fed: AtomicUsize,
+
+    /// Number of spans processed by the worker so far.
+    processed: Mutex<usize>,
+
+    /// Notified whenever the worker finishes a round of processing.
+    progress: Condvar,
+}
+
+/// Guard that marks the worker dead if it exits by panicking, so that no
+/// one waits on it forever.
+struct WorkerGuard<'a, T: Clock>(&'a SharedState<T>);
+
+impl<T: Clock> Drop for WorkerGuard<'_, T> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            *self.0.processed.lock().unwrap_or_else(|e| e.into_inner()) = WORKER_DEAD;
+            self.0.progress.notify_all();
+        }
+    }
+}
+
+/// Thread-safe (`Send + Sync`) linearizer handle. Feeding only appends the
+/// span to its node's own shard, so threads feeding different nodes barely
+/// contend; a background worker thread drains the shards and steps the
+/// wrapped `Linearizer`.
+///
+/// Verdicts are thus reported asynchronously: `.is_ok()` reflects the spans
+/// processed so far, while `.sync()` waits for all spans fed before it.
+#[derive(Debug)]
+pub struct SharedLinearizer<T: Clock + Send + 'static = Timestamp> {
+    /// Number of nodes.
+    num_nodes: usize,
+
+    /// State shared with the worker.
+    state: Arc<SharedState<T>>,
+
+    /// Handle of the worker thread, to unpark it.
+    worker_thread: Thread,
+
+    /// Join handle of the worker, returning the linearizer on exit.
+    worker: Option<JoinHandle<Linearizer<T>>>,
+}
+
+impl<T: Clock + Send + 'static> SharedLinearizer<T> {
+    /// Wrap the given linearizer and spawn its background worker.
+    pub fn new(linearizer: Linearizer<T>) -> Self {
+        let num_nodes = linearizer.num_nodes;
+        let state = Arc::new(SharedState {
+            shards: (0..num_nodes).map(|_| Mutex::new(vec![])).collect(),
+            dirty: AtomicBool::new(false),
+            shutdown: AtomicBool::new(false),
+            ok: AtomicBool::new(linearizer.verdict() != Verdict::Violated),
+            fed: AtomicUsize::new(0),
+            processed: Mutex::new(0),
+            progress: Condvar::new(),
+        });
+
+        let worker_state = state.clone();
+        let worker = thread::Builder::new()
+            .name("linearizer-worker".into())
+            .spawn(move || Self::worker_loop(worker_state, linearizer))
+            .expect("failed to spawn linearizer worker");
+        SharedLinearizer {
+            num_nodes,
+            state,
+            worker_thread: worker.thread().clone(),
+            worker: Some(worker),
+        }
+    }
+
+    /// Worker thread main loop.
+    fn worker_loop(state: Arc<SharedState<T>>, mut linearizer: Linearizer<T>) -> Linearizer<T> {
+        let _guard = WorkerGuard(&state);
+        loop {
+            let shutdown = state.shutdown.load(Ordering::SeqCst);
+            state.dirty.store(false, Ordering::SeqCst);
+
+            let mut num_processed = 0;
+            for (node, shard) in state.shards.iter().enumerate() {
+                let spans = mem::take(&mut *shard.lock().unwrap());
+                num_processed += spans.len();
+                for span in spans {
+                    let ok = linearizer.feed_span(node, span);
+                    state.ok.store(ok, Ordering::SeqCst);
+                }
+            }
+            if num_processed > 0 {
+                *state.processed.lock().unwrap() += num_processed;
+                state.progress.notify_all();
+            }
+
+            if shutdown {
+                // everything fed before shutdown has been drained above
+                return linearizer;
+            }
+            if !state.dirty.load(Ordering::SeqCst) {
+                thread::park();
+            }
+        }
+    }
+
+    /// Get the number of nodes.
+    pub fn num_nodes(&self) -> usize {
+        self.num_nodes
+    }
+
+    /// Feed in a new operation span; can be called concurrently from many
+    /// threads, as long as spans of the same node are fed in order.
+    pub fn feed_span(&self, node: Node, span: OpSpan<T>) {
+        assert!(node < self.num_nodes);
+        assert!(span.ts_req.happens_before(&span.ts_ack) || (!span.is_normal()));
+
+        self.state.shards[node].lock().unwrap().push(span);
+        self.state.fed.fetch_add(1, Ordering::SeqCst);
+        if !self.state.dirty.swap(true, Ordering::SeqCst) {
+            self.worker_thread.unpark();
+        }
+    }
+
+    /// Check if linearizability still holds after the spans processed by the
+    /// worker so far.
+    pub fn is_ok(&self) -> bool {
+        self.state.ok.load(Ordering::SeqCst)
+    }
+
+    /// Wait until all spans fed before this call have been processed, then
+    /// return the verdict.
+    pub fn sync(&self) -> bool {
+        let fed = self.state.fed.load(Ordering::SeqCst);
+        let mut processed = self.state.processed.lock().unwrap();
+        while *processed < fed {
+            processed = self.state.progress.wait(processed).unwrap();
+        }
+        assert_ne!(*processed, WORKER_DEAD, "linearizer worker panicked");
+        self.is_ok()
+    }
+
+    /// Process all spans fed, stop the worker, and return the wrapped
+    /// linearizer.
+    pub fn finish(mut self) -> Linearizer<T> {
+        self.stop_worker()
+            .unwrap_or_else(|e| panic::resume_unwind(e))
+    }
+
+    /// Signal the worker to exit and join it.
+    fn stop_worker(&mut self) -> thread::Result<Linearizer<T>> {
+        let worker = self.worker.take().unwrap();
+        self.state.shutdown.store(true, Ordering::SeqCst);
+        self.worker_thread.unpark();
+        worker.join()
+    }
+}
+
+impl<T: Clock + Send + 'static> Drop for SharedLinearizer<T> {
+    fn drop(&mut self) {
+        if self.worker.is_some() {
+            let _ = self.stop_worker();
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Checkpointing of linearizer state into snapshots, enabled by the `serde`
+//! feature, so that long-running checks can survive restarts of the checker
+//! process.
+
+use std::io::{self, Read, Write};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{Clock, Linearizer};
+
+/// Version of the snapshot format; bumped on incompatible changes.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Versioned snapshot wrapper.
+#[derive(Serialize, Deserialize)]
+struct Snapshot<L> {
+    version: u32,
+    linearizer: L,
+}
+
+impl<T: Clock + Serialize> Linearizer<T> {
+    /// Save a snapshot of the complete checker state, including all current
+    /// possibilities, as JSON. Spans fed later can be fed into the restored
+    /// linearizer as if no restart happened.
+    pub fn checkpoint(&self, writer: impl Write) -> io::Result<()> {
+        serde_json::to_writer(
+            writer,
+            &Snapshot {
+                version: SNAPSHOT_VERSION,
+                linearizer: self,
+            },
+        )?;
+        Ok(())
+    }
+}
+
+impl<T: Clock + DeserializeOwned> Linearizer<T> {
+    /// Restore a linearizer from a snapshot saved by `checkpoint()`.
+    pub fn restore(reader: impl Read) -> io::Result<Self> {
+        let snapshot: Snapshot<Linearizer<T>> = serde_json::from_reader(reader)?;
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(invalid(format!(
+                "unsupported snapshot version {}",
+                snapshot.version
+            )));
+        }
+        let linearizer = snapshot.linearizer;
+        if linearizer.num_nodes == 0
+            || linearizer
+                .possibilities
+                .iter()
+                .any(|p| p.num_nodes() != linearizer.num_nodes)
+        {
+            return Err(invalid("inconsistent number of nodes in snapshot".into()));
+        }
+        if linearizer.violation.is_some() && !linearizer.possibilities.is_empty() {
+            return Err(invalid("inconsistent violation state in snapshot".into()));
+        }
+        Ok(linearizer)
+    }
+}
@@ -0,0 +1,57 @@
+//! Statistics of a linearizer, for monitoring its health during long runs.
+
+use std::fmt;
+use std::time::Duration;
+
+/// Snapshot of checker statistics, as returned by `Linearizer::stats()`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats {
+    /// Number of current possibilities.
+    pub num_possibilities: usize,
+
+    /// Largest number of possibilities at any point so far, including ones
+    /// pending to step within a `feed_span()` call.
+    pub peak_possibilities: usize,
+
+    /// Node-indexed maximum queue depth across current possibilities.
+    pub max_queue_depths: Vec<usize>,
+
+    /// Node-indexed average queue depth across current possibilities.
+    pub avg_queue_depths: Vec<f64>,
+
+    /// Total number of spans fed in, including stop and resume markers.
+    pub spans_fed: usize,
+
+    /// Total number of ops settled, i.e., committed in all possibilities.
+    pub ops_settled: usize,
+
+    /// Total time spent stepping possibilities.
+    pub step_time: Duration,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "possibilities {} (peak {}), fed {}, settled {}, stepping {:?}, queue depths [",
+            self.num_possibilities,
+            self.peak_possibilities,
+            self.spans_fed,
+            self.ops_settled,
+            self.step_time
+        )?;
+        for (i, (max, avg)) in self
+            .max_queue_depths
+            .iter()
+            .zip(self.avg_queue_depths.iter())
+            .enumerate()
+        {
+            write!(f, "{:.1}/{}", avg, max)?;
+            if i < self.max_queue_depths.len() - 1 {
+                write!(f, ",")?;
+            }
+        }
+        write!(f, "]")
+    }
+}
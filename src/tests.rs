@@ -99,3 +99,1359 @@ fn no_partial_stepped() {
         }
     }
 }
+
+#[test]
+fn sequential_ignores_real_time() {
+    let node_ops: [(Node, OpSpan); 5] = [
+        (0, OpSpan::put(8, 100, 105)),
+        (1, OpSpan::get(None, 110, 112)),
+        (1, OpSpan::get(Some(8), 113, 115)),
+        (0, OpSpan::stopped(116)),
+        (1, OpSpan::stopped(117)),
+    ];
+
+    // stale read of nil after Put(8) finished violates linearizability...
+    let mut linearizer = Linearizer::new(2);
+    let ok = node_ops
+        .iter()
+        .all(|(node, span)| linearizer.feed_span(*node, span.clone()));
+    assert!(!ok);
+
+    // ...but is fine under sequential consistency
+    let mut linearizer = Linearizer::new(2).with_model(ConsistencyModel::Sequential);
+    for (node, span) in node_ops {
+        assert!(linearizer.feed_span(node, span));
+    }
+}
+
+#[test]
+fn sequential_keeps_program_order() {
+    let mut linearizer = Linearizer::new(2).with_model(ConsistencyModel::Sequential);
+    let node_ops: [(Node, OpSpan); 5] = [
+        (0, OpSpan::put(8, 100, 105)),
+        (0, OpSpan::put(9, 106, 107)),
+        (1, OpSpan::get(Some(9), 110, 112)),
+        (1, OpSpan::get(Some(8), 113, 115)),
+        (0, OpSpan::stopped(116)),
+    ];
+    let ok = node_ops
+        .into_iter()
+        .all(|(node, span)| linearizer.feed_span(node, span));
+    assert!(!ok);
+}
+
+#[test]
+fn session_guarantees_hold() {
+    let mut checker = SessionChecker::new(2);
+    let node_ops: [(Node, OpSpan); 5] = [
+        (0, OpSpan::put(8, 100, 105)),
+        (1, OpSpan::get(None, 106, 107)),
+        (0, OpSpan::get(Some(8), 108, 109)),
+        (1, OpSpan::get(Some(8), 110, 112)),
+        (1, OpSpan::put(9, 113, 115)),
+    ];
+    for (node, span) in node_ops {
+        checker.feed_span(node, span).unwrap();
+    }
+    let report = checker.report();
+    assert!(SessionGuarantee::ALL.iter().all(|&g| report.holds(g)));
+    assert_eq!(report.pending_gets, 0);
+}
+
+#[test]
+fn session_guarantees_violated() {
+    let mut checker = SessionChecker::new(3);
+    let node_ops: [(Node, OpSpan); 9] = [
+        (2, OpSpan::get(Some(9), 99, 101)),
+        (0, OpSpan::put(7, 100, 102)),
+        (0, OpSpan::get(None, 103, 104)),
+        (1, OpSpan::get(Some(7), 105, 106)),
+        (1, OpSpan::put(8, 107, 108)),
+        (0, OpSpan::put(9, 109, 110)),
+        (2, OpSpan::get(Some(8), 111, 112)),
+        (2, OpSpan::get(Some(7), 113, 114)),
+        (1, OpSpan::get(Some(7), 115, 116)),
+    ];
+    for (node, span) in node_ops {
+        checker.feed_span(node, span).unwrap();
+    }
+    let report = checker.report();
+    assert!(!report.read_your_writes);
+    assert!(!report.monotonic_reads);
+    assert!(!report.monotonic_writes);
+    assert!(!report.writes_follow_reads);
+    assert_eq!(report.pending_gets, 0);
+}
+
+#[test]
+fn session_invalid_input() {
+    let mut checker = SessionChecker::new(2);
+    checker.feed_span(0, OpSpan::put(8, 100, 105)).unwrap();
+    assert!(matches!(
+        checker.feed_span(1, OpSpan::put(8, 106, 107)),
+        Err(HistoryError::Invalid(_))
+    ));
+    assert!(matches!(
+        checker.feed_span(0, OpSpan::get(Some(8), 104, 108)),
+        Err(HistoryError::Invalid(_))
+    ));
+    assert!(matches!(
+        checker.feed_span(2, OpSpan::get(Some(8), 110, 111)),
+        Err(HistoryError::Invalid(_))
+    ));
+
+    // a Get of a value never written is reported once its node stops
+    checker
+        .feed_span(1, OpSpan::get(Some(9), 108, 109))
+        .unwrap();
+    let report = checker
+        .feed_span(1, OpSpan::get(Some(8), 110, 111))
+        .unwrap();
+    assert_eq!((report.pending_gets, report.unwritten_gets), (2, 0));
+    let report = checker.feed_span(1, OpSpan::stopped(112)).unwrap();
+    assert_eq!((report.pending_gets, report.unwritten_gets), (0, 1));
+    assert!(SessionGuarantee::ALL.iter().all(|&g| report.holds(g)));
+}
+
+#[test]
+fn k_atomic_stale_reads() {
+    let node_ops: [(Node, OpSpan); 7] = [
+        (0, OpSpan::put(7, 100, 102)),
+        (0, OpSpan::put(8, 103, 105)),
+        (0, OpSpan::put(9, 106, 108)),
+        (1, OpSpan::get(Some(8), 110, 112)),
+        (1, OpSpan::get(Some(7), 113, 115)),
+        (0, OpSpan::stopped(116)),
+        (1, OpSpan::stopped(117)),
+    ];
+    let feed_all = |mut linearizer: Linearizer| {
+        node_ops
+            .iter()
+            .all(|(node, span)| linearizer.feed_span(*node, span.clone()))
+    };
+    assert!(!feed_all(Linearizer::new(2)));
+    assert!(!feed_all(
+        Linearizer::new(2).with_model(ConsistencyModel::KAtomic { k: 2 })
+    ));
+    assert!(feed_all(
+        Linearizer::new(2).with_model(ConsistencyModel::KAtomic { k: 3 })
+    ));
+}
+
+#[test]
+fn delta_atomic_stale_reads() {
+    let node_ops: [(Node, OpSpan); 5] = [
+        (0, OpSpan::put(7, 100, 102)),
+        (0, OpSpan::put(8, 103, 105)),
+        (1, OpSpan::get(Some(7), 110, 112)),
+        (0, OpSpan::stopped(113)),
+        (1, OpSpan::stopped(114)),
+    ];
+    let feed_all = |mut linearizer: Linearizer| {
+        node_ops
+            .iter()
+            .all(|(node, span)| linearizer.feed_span(*node, span.clone()))
+    };
+    assert!(!feed_all(
+        Linearizer::new(2).with_model(ConsistencyModel::DeltaAtomic { delta: 4 })
+    ));
+    assert!(feed_all(
+        Linearizer::new(2).with_model(ConsistencyModel::DeltaAtomic { delta: 5 })
+    ));
+}
+
+#[test]
+fn regular_register_reads() {
+    let feed_all = |mut linearizer: Linearizer, node_ops: &[(Node, OpSpan)]| {
+        node_ops
+            .iter()
+            .all(|(node, span)| linearizer.feed_span(*node, span.clone()))
+    };
+
+    // new/old inversion: allowed by regular but not atomic semantics
+    let node_ops: [(Node, OpSpan); 5] = [
+        (0, OpSpan::put(7, 100, 110)),
+        (1, OpSpan::get(Some(7), 101, 103)),
+        (1, OpSpan::get(None, 104, 106)),
+        (0, OpSpan::stopped(111)),
+        (1, OpSpan::stopped(112)),
+    ];
+    assert!(!feed_all(Linearizer::new(2), &node_ops));
+    assert!(feed_all(
+        Linearizer::new(2).with_model(ConsistencyModel::Regular),
+        &node_ops
+    ));
+
+    // garbage value during overlap: allowed by safe but not regular semantics
+    let node_ops: [(Node, OpSpan); 5] = [
+        (0, OpSpan::put(7, 100, 110)),
+        (1, OpSpan::get(Some(9), 101, 103)),
+        (1, OpSpan::get(Some(7), 111, 113)),
+        (0, OpSpan::stopped(114)),
+        (1, OpSpan::stopped(115)),
+    ];
+    assert!(!feed_all(
+        Linearizer::new(2).with_model(ConsistencyModel::Regular),
+        &node_ops
+    ));
+    assert!(feed_all(
+        Linearizer::new(2).with_model(ConsistencyModel::Safe),
+        &node_ops
+    ));
+
+    // no overlap at all: even safe semantics requires the last value
+    let node_ops: [(Node, OpSpan); 4] = [
+        (0, OpSpan::put(7, 100, 102)),
+        (1, OpSpan::get(Some(9), 103, 105)),
+        (0, OpSpan::stopped(106)),
+        (1, OpSpan::stopped(107)),
+    ];
+    assert!(!feed_all(
+        Linearizer::new(2).with_model(ConsistencyModel::Safe),
+        &node_ops
+    ));
+}
+
+#[test]
+fn with_clock_skew() {
+    let node_ops: [(Node, OpSpan); 4] = [
+        (0, OpSpan::put(8, 100, 105)),
+        (1, OpSpan::get(None, 107, 109)),
+        (0, OpSpan::stopped(110)),
+        (1, OpSpan::stopped(111)),
+    ];
+    let feed_all = |mut linearizer: Linearizer| {
+        node_ops
+            .iter()
+            .all(|(node, span)| linearizer.feed_span(*node, span.clone()))
+    };
+    assert!(!feed_all(Linearizer::new(2)));
+    assert!(!feed_all(Linearizer::new(2).with_max_clock_skew(2)));
+    assert!(feed_all(Linearizer::new(2).with_max_clock_skew(3)));
+}
+
+#[test]
+fn with_vector_clocks() {
+    let vc = |c: [u64; 2]| VectorTimestamp(c.to_vec());
+
+    // Get concurrent with the Put in happens-before order may miss it
+    let mut linearizer = Linearizer::new(2);
+    let node_ops: [(Node, OpSpan<VectorTimestamp>); 5] = [
+        (0, OpSpan::put(8, vc([1, 0]), vc([2, 0]))),
+        (1, OpSpan::get(None, vc([0, 1]), vc([0, 2]))),
+        (1, OpSpan::get(Some(8), vc([2, 3]), vc([2, 4]))),
+        (0, OpSpan::stopped(vc([3, 0]))),
+        (1, OpSpan::stopped(vc([2, 5]))),
+    ];
+    for (node, span) in node_ops {
+        assert!(linearizer.feed_span(node, span));
+    }
+
+    // but not if the Get causally follows the Put
+    let mut linearizer = Linearizer::new(2);
+    let node_ops: [(Node, OpSpan<VectorTimestamp>); 4] = [
+        (0, OpSpan::put(8, vc([1, 0]), vc([2, 0]))),
+        (1, OpSpan::get(None, vc([2, 1]), vc([2, 2]))),
+        (0, OpSpan::stopped(vc([3, 0]))),
+        (1, OpSpan::stopped(vc([2, 3]))),
+    ];
+    let ok = node_ops
+        .into_iter()
+        .all(|(node, span)| linearizer.feed_span(node, span));
+    assert!(!ok);
+}
+
+#[test]
+fn with_hybrid_clocks() {
+    let hlc = HybridTimestamp::new;
+    let node_ops: [(Node, OpSpan<HybridTimestamp>); 4] = [
+        (0, OpSpan::put(8, hlc(100, 0), hlc(100, 2))),
+        (1, OpSpan::get(None, hlc(100, 3), hlc(101, 0))),
+        (0, OpSpan::stopped(hlc(102, 0))),
+        (1, OpSpan::stopped(hlc(102, 1))),
+    ];
+    let feed_all = |mut linearizer: Linearizer<HybridTimestamp>| {
+        node_ops
+            .iter()
+            .all(|(node, span)| linearizer.feed_span(*node, span.clone()))
+    };
+    assert!(!feed_all(Linearizer::new(2)));
+    assert!(feed_all(Linearizer::new(2).with_max_clock_skew(1)));
+}
+
+#[test]
+fn recorder_threads() {
+    use std::sync::Mutex;
+    use std::thread;
+
+    let register = Mutex::new(None);
+    let recorder = Recorder::with_clock(Linearizer::new(4), RecorderClock::Instant);
+    thread::scope(|s| {
+        for t in 0..3 {
+            let (register, recorder) = (&register, recorder.clone());
+            s.spawn(move || {
+                for i in 0..10 {
+                    let val = t * 100 + i;
+                    recorder.put(val, || *register.lock().unwrap() = Some(val));
+                    recorder.get(|| *register.lock().unwrap());
+                }
+                recorder.stop();
+            });
+        }
+    });
+    assert!(recorder.is_ok());
+    assert!(recorder.finish());
+}
+
+#[test]
+fn recorder_stale_get() {
+    let recorder = Recorder::new(Linearizer::new(2));
+    recorder.put(7, || ());
+    recorder.get(|| None);
+    assert!(!recorder.finish());
+}
+
+#[test]
+fn shared_concurrent_feeds() {
+    use std::thread;
+
+    fn assert_send_sync<S: Send + Sync>(_: &S) {}
+
+    let shared = SharedLinearizer::new(Linearizer::new(3));
+    assert_send_sync(&shared);
+    let node_ops: [(Node, OpSpan); 11] = [
+        (2, OpSpan::get(None, 99, 101)),
+        (0, OpSpan::put(8, 100, 105)),
+        (1, OpSpan::put(7, 104, 106)),
+        (2, OpSpan::get(Some(7), 102, 108)),
+        (1, OpSpan::get(Some(8), 110, 112)),
+        (2, OpSpan::get(Some(9), 109, 115)),
+        (0, OpSpan::get(Some(8), 111, 117)),
+        (1, OpSpan::put(9, 114, 118)),
+        (2, OpSpan::stopped(116)),
+        (1, OpSpan::stopped(120)),
+        (0, OpSpan::stopped(119)),
+    ];
+    thread::scope(|s| {
+        for n in 0..3 {
+            let (shared, node_ops) = (&shared, &node_ops);
+            s.spawn(move || {
+                for (node, span) in node_ops.iter().filter(|(node, _)| *node == n) {
+                    shared.feed_span(*node, span.clone());
+                }
+            });
+        }
+    });
+    assert!(shared.sync());
+    assert!(shared.finish().feed_span(0, OpSpan::resumed(121)));
+}
+
+#[test]
+fn shared_detect_violation() {
+    let shared = SharedLinearizer::new(Linearizer::new(2));
+    shared.feed_span(0, OpSpan::put(8, 100, 105));
+    shared.feed_span(1, OpSpan::get(Some(7), 106, 108));
+    shared.feed_span(0, OpSpan::stopped(109));
+    assert!(!shared.sync());
+    assert!(!shared.is_ok());
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn service_detect_violation() {
+    let service = CheckerService::spawn(Linearizer::new(2));
+    let sender = service.sender();
+    let node_ops: [(Node, OpSpan); 4] = [
+        (0, OpSpan::put(8, 100, 105)),
+        (1, OpSpan::get(Some(8), 106, 108)),
+        (1, OpSpan::get(Some(7), 109, 110)),
+        (0, OpSpan::stopped(111)),
+    ];
+    for (node, span) in node_ops {
+        sender.send((node, span)).unwrap();
+    }
+    drop(sender);
+    let verdict = service.verdict();
+    assert!(service.violation().await.unwrap());
+    assert!(!*verdict.borrow());
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn service_no_violation() {
+    let service = CheckerService::spawn(Linearizer::new(2));
+    let sender = service.sender();
+    let node_ops: [(Node, OpSpan); 4] = [
+        (0, OpSpan::put(8, 100, 105)),
+        (1, OpSpan::get(Some(8), 106, 108)),
+        (1, OpSpan::stopped(109)),
+        (0, OpSpan::stopped(110)),
+    ];
+    for (node, span) in node_ops {
+        sender.send((node, span)).unwrap();
+    }
+    drop(sender);
+    let verdict = service.verdict();
+    assert!(!service.violation().await.unwrap());
+    assert!(*verdict.borrow());
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn service_concurrent_tasks() {
+    let service = CheckerService::spawn(Linearizer::new(2));
+    let tasks: Vec<_> = (0..2)
+        .map(|node| {
+            let sender = service.sender();
+            tokio::spawn(async move {
+                let base = 100 * (node as Timestamp + 1);
+                sender
+                    .send((node, OpSpan::put(node as Value, base, base + 1)))
+                    .unwrap();
+                sender.send((node, OpSpan::stopped(base + 2))).unwrap();
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+    let linearizer = service.finish().await;
+    assert!(!linearizer.possibilities.is_empty());
+}
+
+#[test]
+fn history_text_format() {
+    let text = "\
+# comment line
+0 put 8 100 105
+1 get nil 101 103   # trailing comment
+1 fail 104 107
+0 stopped 108
+0 resumed 110
+0 get 8 111 112
+";
+    let mut history = History::read_text(text.as_bytes()).unwrap();
+    assert_eq!(history.num_nodes(), 2);
+    assert_eq!(history.events().len(), 6);
+    history.close();
+    assert_eq!(history.events().len(), 8);
+
+    let mut output = vec![];
+    history.write_text(&mut output).unwrap();
+    let reread = History::read_text(output.as_slice()).unwrap();
+    assert_eq!(
+        format!("{:?}", reread.events()),
+        format!("{:?}", history.events())
+    );
+    assert_eq!(history.feed_into(&mut Linearizer::new(2)), None);
+
+    assert!(matches!(
+        History::read_text("0 put 8 100".as_bytes()),
+        Err(HistoryError::Parse { line: 1, .. })
+    ));
+    assert!(matches!(
+        History::read_text("0 put 8 100 105\n0 get 8 104 106".as_bytes()),
+        Err(HistoryError::Parse { line: 2, .. })
+    ));
+
+    // huge node IDs are rejected instead of overflowing or allocating
+    for node in ["18446744073709551615", "4000000000", "65536"] {
+        let text = format!("0 put 8 100 105\n{} put 1 1 2", node);
+        assert!(matches!(
+            History::read_text(text.as_bytes()),
+            Err(HistoryError::Parse { line: 2, .. })
+        ));
+    }
+    assert!(History::read_text("65535 put 1 1 2".as_bytes()).is_ok());
+}
+
+#[test]
+fn violation_report() {
+    let mut linearizer = Linearizer::new(2);
+    let node_ops: [(Node, OpSpan); 4] = [
+        (0, OpSpan::put(55, 1, 5)),
+        (1, OpSpan::put(66, 3, 6)),
+        (1, OpSpan::get(Some(77), 10, 12)),
+        (0, OpSpan::put(77, 13, 14)),
+    ];
+    for (node, span) in node_ops {
+        linearizer.feed_span(node, span);
+    }
+    let violation = linearizer.violation().unwrap();
+    assert_eq!(violation.node, 0);
+    assert_eq!(violation.linearized.len(), 2);
+    assert_eq!(violation.unlinearized.len(), 2);
+    assert!(linearizer.witness().is_none());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn history_jsonl_format() {
+    let jsonl = r#"
+{"node":0,"type":"put","value":8,"ts_req":100,"ts_ack":105}
+{"node":1,"type":"get","value":null,"ts_req":101,"ts_ack":103}
+{"node":1,"type":"fail","ts_req":104,"ts_ack":107}
+{"node":0,"type":"stopped","ts_req":108}
+{"node":1,"type":"get","ts_req":109,"ts_ack":110}
+"#;
+    let history: History = History::read_jsonl(jsonl.as_bytes()).unwrap();
+    assert_eq!(history.num_nodes(), 2);
+    assert_eq!(history.events().len(), 5);
+
+    let mut output = vec![];
+    history.write_jsonl(&mut output).unwrap();
+    let reread: History = History::read_jsonl(output.as_slice()).unwrap();
+    assert_eq!(
+        format!("{:?}", reread.events()),
+        format!("{:?}", history.events())
+    );
+
+    let jsonl = r#"{"node":0,"type":"put","ts_req":[1,0],"ts_ack":[2,0]}"#;
+    assert!(matches!(
+        History::<VectorTimestamp>::read_jsonl(jsonl.as_bytes()),
+        Err(HistoryError::Parse { line: 1, .. })
+    ));
+    let jsonl = r#"{"node":0,"type":"put","value":3,"ts_req":[1,0],"ts_ack":[2,0]}"#;
+    let history = History::<VectorTimestamp>::read_jsonl(jsonl.as_bytes()).unwrap();
+    assert_eq!(history.events().len(), 1);
+}
+
+#[test]
+fn history_jepsen_import() {
+    let edn = r#"
+[{:type :invoke, :f :write, :value 3, :process 0, :time 1000, :index 0}
+ {:type :invoke, :f :read, :value nil, :process 1, :time 1100, :index 1}
+ {:type :ok, :f :write, :value 3, :process 0, :time 1500, :index 2}
+ {:type :ok, :f :read, :value 3, :process 1, :time 1700, :index 3}
+ {:type :info, :f :start, :value nil, :process :nemesis, :index 4}
+ {:type :invoke, :f :cas, :value [3 4], :process 0, :time 1800, :index 5}
+ {:type :invoke, :f :cas, :value [5 6], :process 1, :time 1850, :index 6}
+ {:type :fail, :f :cas, :value [5 6], :process 1, :time 1880, :index 7}
+ {:type :ok, :f :cas, :value [3 4], :process 0, :time 1900, :index 8}
+ {:type :invoke, :f :write, :value 7, :process 1, :time 2000, :index 9}
+ {:type :info, :f :write, :value 7, :process 1, :time 2100, :index 10,
+  :error "timeout"}
+ {:type :invoke, :f :read, :value nil, :process 2, :time 2200, :index 11}
+ {:type :ok, :f :read, :value 7, :process 2, :time 2300, :index 12}
+ {:type :invoke, :f :read, :value nil, :process 0, :time 2400, :index 13}
+ {:type :ok, :f :read, :value 7, :process 0, :time 2500, :index 14}]
+"#;
+    let mut history = History::read_jepsen(edn.as_bytes()).unwrap();
+    history.close();
+    assert_eq!(history.num_nodes(), 3);
+    let mut linearizer = Linearizer::new(history.num_nodes());
+    assert_eq!(history.feed_into(&mut linearizer), None);
+
+    // a read cannot observe a cas invoked after it completed
+    let edn = edn.replace(
+        ":value 3, :process 1, :time 1700",
+        ":value 4, :process 1, :time 1700",
+    );
+    let mut history = History::read_jepsen(edn.as_bytes()).unwrap();
+    history.close();
+    let mut linearizer = Linearizer::new(history.num_nodes());
+    assert!(history.feed_into(&mut linearizer).is_some());
+
+    // an indeterminate write either took effect or did not, so a read of a
+    // value never written is still rejected
+    let edn = r#"
+{:type :invoke, :f :write, :value 3, :process 0, :index 0}
+{:type :info, :f :write, :value 3, :process 0, :index 1}
+{:type :invoke, :f :read, :value nil, :process 1, :index 2}
+{:type :ok, :f :read, :value 99, :process 1, :index 3}
+"#;
+    let mut history = History::read_jepsen(edn.as_bytes()).unwrap();
+    history.close();
+    let mut linearizer = Linearizer::new(history.num_nodes());
+    assert!(history.feed_into(&mut linearizer).is_some());
+    for val in ["3", "nil"] {
+        let edn = edn.replace(":value 99", &format!(":value {}", val));
+        let mut history = History::read_jepsen(edn.as_bytes()).unwrap();
+        history.close();
+        let mut linearizer = Linearizer::new(history.num_nodes());
+        assert_eq!(history.feed_into(&mut linearizer), None);
+    }
+
+    // likewise an indeterminate cas, which may also take effect unobserved
+    // after the end of the history even if its old value is gone by then
+    let edn = r#"
+{:type :invoke, :f :write, :value 1, :process 0, :index 0}
+{:type :ok, :f :write, :value 1, :process 0, :index 1}
+{:type :invoke, :f :cas, :value [1 2], :process 0, :index 2}
+{:type :info, :f :cas, :value [1 2], :process 0, :index 3}
+{:type :invoke, :f :read, :value nil, :process 1, :index 4}
+{:type :ok, :f :read, :value 2, :process 1, :index 5}
+{:type :invoke, :f :read, :value nil, :process 1, :index 6}
+{:type :ok, :f :read, :value 1, :process 1, :index 7}
+"#;
+    let mut history = History::read_jepsen(edn.as_bytes()).unwrap();
+    history.close();
+    assert_eq!(history.num_nodes(), 3);
+    let mut linearizer = Linearizer::new(history.num_nodes());
+    assert!(history.feed_into(&mut linearizer).is_some());
+    for (first, second) in [("1", "1"), ("1", "2"), ("2", "2")] {
+        let edn = edn
+            .replace(
+                ":value 2, :process 1",
+                &format!(":value {}, :process 1", first),
+            )
+            .replace(
+                ":value 1, :process 1",
+                &format!(":value {}, :process 1", second),
+            );
+        let mut history = History::read_jepsen(edn.as_bytes()).unwrap();
+        history.close();
+        let mut linearizer = Linearizer::new(history.num_nodes());
+        assert_eq!(
+            history.feed_into(&mut linearizer),
+            None,
+            "{} {}",
+            first,
+            second
+        );
+    }
+    let edn = edn.replace(":value 1, :process 1", ":value 5, :process 1");
+    let mut history = History::read_jepsen(edn.as_bytes()).unwrap();
+    history.close();
+    let mut linearizer = Linearizer::new(history.num_nodes());
+    assert!(history.feed_into(&mut linearizer).is_some());
+
+    // discarded forms may come last in a collection or the input
+    let edn = r#"
+[{:type :invoke, :f :cas, :value [1 2 #_3], :process 0, #_:time}
+ {:type :ok, :f :cas, :value [1 2], :process 0 #_:time #_ 5} #_{:type :info}]
+#_[]
+"#;
+    let history = History::read_jepsen(edn.as_bytes()).unwrap();
+    assert_eq!(history.events().len(), 1);
+
+    assert!(matches!(
+        History::read_jepsen("{:type :ok, :f :read, :value 1, :process 0}".as_bytes()),
+        Err(HistoryError::Parse { line: 1, .. })
+    ));
+}
+
+#[test]
+fn cas_spans() {
+    let mut linearizer = Linearizer::new(2);
+    for (node, span) in [
+        (0, OpSpan::put(1, 1, 2)),
+        (1, OpSpan::cas(1, 2, 3, 6)),
+        (0, OpSpan::cas(2, 3, 4, 5)),
+        (0, OpSpan::get(Some(3), 7, 8)),
+        (0, OpSpan::stopped(9)),
+        (1, OpSpan::stopped(10)),
+    ] {
+        assert!(linearizer.feed_span(node, span));
+    }
+
+    let mut linearizer = Linearizer::new(2);
+    for (node, span) in [
+        (0, OpSpan::put(1, 1, 2)),
+        (1, OpSpan::cas(2, 3, 3, 4)),
+        (0, OpSpan::stopped(5)),
+    ] {
+        linearizer.feed_span(node, span);
+    }
+    assert!(!linearizer.feed_span(1, OpSpan::stopped(6)));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn history_porcupine_import() {
+    let log = r#"[
+{"ClientId":0,"Input":{"Op":"put","Key":"x","Value":"a"},"Call":10,"Output":null,"Return":25},
+{"ClientId":1,"Input":{"Op":"get","Key":"x"},"Call":12,"Output":{"Value":"a"},"Return":20},
+{"ClientId":1,"Input":{"Op":"get","Key":"y"},"Call":21,"Output":{"Value":""},"Return":30},
+{"ClientId":2,"Input":{"Op":"get","Key":"x"},"Call":30,"Output":{"Value":""},"Return":35}
+]"#;
+    let histories = History::read_porcupine(log.as_bytes()).unwrap();
+    assert_eq!(histories.len(), 2);
+    let verdicts: Vec<bool> = histories
+        .into_values()
+        .map(|mut history| {
+            history.close();
+            let mut linearizer = Linearizer::new(history.num_nodes());
+            history.feed_into(&mut linearizer).is_none()
+        })
+        .collect();
+    assert_eq!(verdicts, [false, true]);
+
+    // register model with integer values and boolean ops, one per line
+    let log = r#"
+{"ClientId":0,"Input":{"op":false,"value":100},"Call":0,"Output":0,"Return":10}
+{"ClientId":1,"Input":{"op":true,"value":0},"Call":5,"Output":100,"Return":10}
+"#;
+    let mut histories = History::read_porcupine(log.as_bytes()).unwrap();
+    let mut history = histories.remove("").unwrap();
+    history.close();
+    assert_eq!(history.num_nodes(), 3);
+    let mut linearizer = Linearizer::new(history.num_nodes());
+    assert_eq!(history.feed_into(&mut linearizer), None);
+
+    // numeric registers start at 0, so reading 0 before any write is fine
+    let log = r#"
+{"ClientId":0,"Input":{"op":true,"value":0},"Call":0,"Output":0,"Return":5}
+{"ClientId":1,"Input":{"op":false,"value":7},"Call":6,"Output":0,"Return":10}
+{"ClientId":0,"Input":{"op":true,"value":0},"Call":11,"Output":7,"Return":15}
+"#;
+    let mut history = History::read_porcupine(log.as_bytes())
+        .unwrap()
+        .remove("")
+        .unwrap();
+    history.close();
+    let mut linearizer = Linearizer::new(history.num_nodes());
+    assert_eq!(history.feed_into(&mut linearizer), None);
+    let log = log.replace("\"Output\":7", "\"Output\":0");
+    let mut history = History::read_porcupine(log.as_bytes())
+        .unwrap()
+        .remove("")
+        .unwrap();
+    history.close();
+    let mut linearizer = Linearizer::new(history.num_nodes());
+    assert!(history.feed_into(&mut linearizer).is_some());
+
+    // writing the initial value back is an ordinary write
+    let log = r#"
+{"ClientId":0,"Input":{"Op":"put","Value":"a"},"Call":0,"Output":null,"Return":5}
+{"ClientId":0,"Input":{"Op":"put","Value":""},"Call":6,"Output":null,"Return":10}
+{"ClientId":1,"Input":{"Op":"get"},"Call":11,"Output":{"Value":""},"Return":15}
+"#;
+    let mut history = History::read_porcupine(log.as_bytes())
+        .unwrap()
+        .remove("")
+        .unwrap();
+    history.close();
+    let mut linearizer = Linearizer::new(history.num_nodes());
+    assert_eq!(history.feed_into(&mut linearizer), None);
+    let log = log.replace("{\"Value\":\"\"}", "{\"Value\":\"a\"}");
+    let mut history = History::read_porcupine(log.as_bytes())
+        .unwrap()
+        .remove("")
+        .unwrap();
+    history.close();
+    let mut linearizer = Linearizer::new(history.num_nodes());
+    assert!(history.feed_into(&mut linearizer).is_some());
+}
+
+#[test]
+fn binary_format_stream() {
+    let node_ops: [(Node, OpSpan); 8] = [
+        (0, OpSpan::put(300, 100, 105)),
+        (1, OpSpan::get(None, 101, 103)),
+        (1, OpSpan::fail(104, 107)),
+        (0, OpSpan::cas(300, 1 << 40, 106, 200)),
+        (0, OpSpan::stopped(201)),
+        (0, OpSpan::resumed(210)),
+        (0, OpSpan::get(Some(1 << 40), 211, 212)),
+        (1, OpSpan::stopped(300)),
+    ];
+    let mut writer = BinaryWriter::new(vec![]).unwrap();
+    for (node, span) in &node_ops {
+        writer.write_span(*node, span).unwrap();
+    }
+    assert!(writer.write_span(1, &OpSpan::get(None, 299, 301)).is_err());
+    let bytes = writer.into_inner();
+    assert!(bytes.len() < 4 + 8 * 8);
+
+    let spans: Vec<(Node, OpSpan)> = BinaryReader::new(bytes.as_slice())
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(format!("{:?}", spans), format!("{:?}", node_ops));
+
+    let mut linearizer = Linearizer::new(2);
+    let mut reader = BinaryReader::new(bytes.as_slice()).unwrap();
+    assert_eq!(reader.feed_into(&mut linearizer).unwrap(), None);
+
+    let history = History::read_binary(bytes.as_slice()).unwrap();
+    let mut output = vec![];
+    history.write_binary(&mut output).unwrap();
+    assert_eq!(output, bytes);
+
+    assert!(matches!(
+        History::read_binary(&bytes[..bytes.len() - 1]),
+        Err(HistoryError::Invalid(_))
+    ));
+    assert!(BinaryReader::new("0 put".as_bytes()).is_err());
+
+    // streams breaking per-node rules are rejected instead of panicking
+    let mut writer = BinaryWriter::new(vec![]).unwrap();
+    writer.write_span(0, &OpSpan::stopped(5)).unwrap();
+    assert!(writer.write_span(0, &OpSpan::put(1, 6, 7)).is_err());
+    assert!(writer.write_span(1, &OpSpan::resumed(8)).is_err());
+    let malformed: [&[u8]; 4] = [
+        &[0, 0, 1, 1, 1, 1, 0, 0, 1], // get starting at previous ack
+        &[5, 0, 1, 0, 0, 1, 1, 1],    // put while stopped
+        &[6, 0, 1],                   // resumed first
+        &[
+            5, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, 1,
+        ],
+    ];
+    for record in malformed {
+        let bytes = [b"LNZ1".as_slice(), record].concat();
+        let mut linearizer = Linearizer::new(2);
+        let mut reader = BinaryReader::new(bytes.as_slice()).unwrap();
+        assert!(matches!(
+            reader.feed_into(&mut linearizer),
+            Err(HistoryError::Invalid(_))
+        ));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn checkpoint_restore() {
+    let node_ops: [(Node, OpSpan); 7] = [
+        (0, OpSpan::put(55, 1, 5)),
+        (1, OpSpan::put(66, 3, 6)),
+        (2, OpSpan::fail(2, 7)),
+        (1, OpSpan::get(Some(55), 8, 9)),
+        (0, OpSpan::get(Some(66), 10, 12)),
+        (2, OpSpan::stopped(13)),
+        (1, OpSpan::stopped(14)),
+    ];
+    let mut linearizer = Linearizer::new(3).with_model(ConsistencyModel::KAtomic { k: 2 });
+    for (node, span) in &node_ops[..4] {
+        assert!(linearizer.feed_span(*node, span.clone()));
+    }
+
+    let mut snapshot = vec![];
+    linearizer.checkpoint(&mut snapshot).unwrap();
+    let mut restored = Linearizer::<Timestamp>::restore(snapshot.as_slice()).unwrap();
+    assert_eq!(restored.model(), linearizer.model());
+    assert_eq!(restored.possibilities, linearizer.possibilities);
+    for (node, span) in &node_ops[4..] {
+        assert_eq!(
+            restored.feed_span(*node, span.clone()),
+            linearizer.feed_span(*node, span.clone())
+        );
+    }
+    assert_eq!(restored.witness().is_some(), linearizer.witness().is_some());
+
+    assert!(Linearizer::<Timestamp>::restore("{}".as_bytes()).is_err());
+    let snapshot = String::from_utf8(snapshot).unwrap();
+    let snapshot = snapshot.replacen("\"version\":1", "\"version\":0", 1);
+    assert!(Linearizer::<Timestamp>::restore(snapshot.as_bytes()).is_err());
+}
+
+#[test]
+fn frontier_limits() {
+    // concurrent Puts of distinct values blow up the frontier
+    let puts: Vec<(Node, OpSpan)> = (0..5)
+        .map(|i| (i as Node, OpSpan::put(i as Value + 1, i + 1, i + 100)))
+        .collect();
+    let mut bad_tail: Vec<(Node, OpSpan)> = vec![(0, OpSpan::get(Some(99), 200, 201))];
+    bad_tail.extend((1..5).map(|i| (i as Node, OpSpan::stopped(300 + i))));
+    let limits = Limits {
+        max_possibilities: Some(3),
+        ..Default::default()
+    };
+
+    let mut linearizer = Linearizer::new(5).with_limits(limits);
+    for (node, span) in puts.clone() {
+        assert!(linearizer.feed_span(node, span));
+    }
+    assert_eq!(linearizer.verdict(), Verdict::Unknown);
+    assert!(linearizer.possibilities.is_empty());
+    for (node, span) in bad_tail.clone() {
+        assert!(linearizer.feed_span(node, span));
+    }
+    assert_eq!(linearizer.verdict(), Verdict::Unknown);
+
+    let mut linearizer = Linearizer::new(5).with_limits(Limits {
+        policy: LimitPolicy::Sample { size: 2 },
+        ..limits
+    });
+    for (node, span) in puts.clone() {
+        assert!(linearizer.feed_span(node, span));
+    }
+    assert_eq!(linearizer.verdict(), Verdict::Pass);
+    assert!(linearizer.possibilities.len() <= 3);
+    for (node, span) in bad_tail.clone() {
+        assert!(linearizer.feed_span(node, span));
+    }
+    assert_eq!(linearizer.verdict(), Verdict::Unknown);
+    assert!(linearizer.violation().is_some());
+
+    let mut linearizer = Linearizer::new(5);
+    for (node, span) in puts.clone() {
+        assert!(linearizer.feed_span(node, span));
+    }
+    for (node, span) in bad_tail {
+        linearizer.feed_span(node, span);
+    }
+    assert_eq!(linearizer.verdict(), Verdict::Violated);
+
+    let mut linearizer = Linearizer::new(5).with_limits(Limits {
+        max_memory: Some(1),
+        ..Default::default()
+    });
+    for (node, span) in puts {
+        linearizer.feed_span(node, span);
+    }
+    assert_eq!(linearizer.verdict(), Verdict::Unknown);
+}
+
+#[test]
+fn settled_lineage_gc() {
+    let node_ops: [(Node, OpSpan); 6] = [
+        (0, OpSpan::put(1, 1, 2)),
+        (1, OpSpan::get(Some(1), 3, 4)),
+        (0, OpSpan::put(2, 5, 8)),
+        (1, OpSpan::put(3, 6, 9)),
+        (0, OpSpan::get(Some(3), 10, 11)),
+        (1, OpSpan::get(Some(3), 12, 13)),
+    ];
+    let mut linearizer = Linearizer::new(2).with_keep_settled(true);
+    for (node, span) in &node_ops[..4] {
+        assert!(linearizer.feed_span(*node, span.clone()));
+    }
+    // the two concurrent Puts are not settled yet
+    assert_eq!(linearizer.num_settled(), 2);
+    assert_eq!(linearizer.settled().len(), 2);
+    assert!(linearizer
+        .possibilities
+        .iter()
+        .all(|p| p.lineage().len() == 1));
+    assert_eq!(linearizer.witness().unwrap().len(), 3);
+
+    for (node, span) in &node_ops[4..] {
+        assert!(linearizer.feed_span(*node, span.clone()));
+    }
+    assert_eq!(linearizer.num_settled(), 5);
+    let settled = linearizer.take_settled();
+    assert_eq!(
+        format!(
+            "{:?}",
+            settled[2..4].iter().map(|(n, _)| n).collect::<Vec<_>>()
+        ),
+        "[0, 1]"
+    );
+    assert!(linearizer.settled().is_empty());
+    assert_eq!(linearizer.num_settled(), 5);
+
+    // discarded by default
+    let mut linearizer = Linearizer::new(2);
+    for (node, span) in node_ops {
+        assert!(linearizer.feed_span(node, span));
+    }
+    assert_eq!(linearizer.num_settled(), 5);
+    assert!(linearizer.settled().is_empty());
+}
+
+#[test]
+fn checker_stats() {
+    let mut linearizer = Linearizer::new(3);
+    let stats = linearizer.stats();
+    assert_eq!(stats.num_possibilities, 1);
+    assert_eq!(stats.spans_fed, 0);
+    assert_eq!(stats.max_queue_depths, [0, 0, 0]);
+
+    let node_ops: [(Node, OpSpan); 5] = [
+        (0, OpSpan::put(1, 1, 10)),
+        (1, OpSpan::put(2, 2, 11)),
+        (0, OpSpan::get(Some(2), 12, 13)),
+        (0, OpSpan::get(Some(2), 14, 15)),
+        (2, OpSpan::put(3, 3, 16)),
+    ];
+    for (node, span) in node_ops {
+        assert!(linearizer.feed_span(node, span));
+    }
+    let stats = linearizer.stats();
+    assert_eq!(stats.spans_fed, 5);
+    assert_eq!(stats.num_possibilities, linearizer.possibilities.len());
+    assert!(stats.peak_possibilities >= stats.num_possibilities);
+    assert!(stats.peak_possibilities > 1);
+    assert_eq!(stats.max_queue_depths.len(), 3);
+    assert!(stats.avg_queue_depths[0] <= stats.max_queue_depths[0] as f64);
+    assert!(stats.to_string().starts_with("possibilities "));
+}
+
+#[derive(Default)]
+struct CountingObserver {
+    fed: usize,
+    created: usize,
+    pruned: Vec<PruneReason>,
+    deduplicated: usize,
+    dead_ends: usize,
+    settled: usize,
+}
+
+impl CheckerObserver for CountingObserver {
+    fn on_feed(&mut self, _node: Node, _span: &OpSpan) {
+        self.fed += 1;
+    }
+
+    fn on_created(&mut self, parent: StateView, _node: Node, _op: &OpSpan, child: StateView) {
+        assert_eq!(child.lineage().len(), parent.lineage().len() + 1);
+        self.created += 1;
+    }
+
+    fn on_pruned(&mut self, _parent: StateView, _node: Node, _op: &OpSpan, reason: PruneReason) {
+        self.pruned.push(reason);
+    }
+
+    fn on_deduplicated(&mut self, _state: StateView) {
+        self.deduplicated += 1;
+    }
+
+    fn on_dead_end(&mut self, _state: StateView) {
+        self.dead_ends += 1;
+    }
+
+    fn on_settled(&mut self, ops: &[(Node, OpSpan)]) {
+        self.settled += ops.len();
+    }
+}
+
+#[test]
+fn observer_hooks() {
+    let mut observer = CountingObserver::default();
+    let mut linearizer = Linearizer::new(2);
+    let node_ops: [(Node, OpSpan); 6] = [
+        (0, OpSpan::put(1, 1, 10)),
+        (1, OpSpan::put(2, 2, 11)),
+        (0, OpSpan::fail(12, 13)),
+        (1, OpSpan::fail(14, 15)),
+        (0, OpSpan::get(Some(1), 16, 17)),
+        (1, OpSpan::cas(7, 8, 18, 19)),
+    ];
+    for (node, span) in node_ops {
+        assert!(linearizer.feed_span_with(node, span, &mut observer));
+    }
+    assert_eq!(observer.fed, 6);
+    assert!(observer.created > 0);
+    assert!(observer.deduplicated > 0);
+    assert_eq!(observer.settled, linearizer.num_settled());
+
+    let mut linearizer = Linearizer::new(2);
+    let node_ops: [(Node, OpSpan); 3] = [
+        (0, OpSpan::put(1, 1, 2)),
+        (1, OpSpan::get(Some(2), 3, 4)),
+        (0, OpSpan::stopped(5)),
+    ];
+    let mut observer = CountingObserver::default();
+    for (node, span) in node_ops {
+        linearizer.feed_span_with(node, span, &mut observer);
+    }
+    assert_eq!(linearizer.verdict(), Verdict::Violated);
+    assert_eq!(observer.pruned, [PruneReason::GetMismatch]);
+    assert_eq!(observer.dead_ends, 1);
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn tracing_events() {
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata};
+
+    /// Visitor formatting fields as `name=value`, the message first.
+    #[derive(Default)]
+    struct Fields(String);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            if field.name() == "message" {
+                self.0.insert_str(0, &format!("{:?}", value));
+            } else {
+                self.0.push_str(&format!(" {}={:?}", field.name(), value));
+            }
+        }
+    }
+
+    /// Subscriber that records spans and events with their fields.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<(Vec<String>, Vec<String>)>>);
+
+    impl tracing::Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            let mut log = self.0.lock().unwrap();
+            log.0
+                .push(format!("{}{}", attrs.metadata().name(), fields.0));
+            Id::from_u64(log.0.len() as u64)
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.0.lock().unwrap().1.push(fields.0);
+        }
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let mut linearizer = Linearizer::new(2);
+        linearizer.feed_span(0, OpSpan::put(1, 1, 2));
+        linearizer.feed_span(1, OpSpan::get(Some(2), 3, 4));
+        linearizer.feed_span(0, OpSpan::stopped(5));
+    });
+    let (spans, events) = recorder.0.lock().unwrap().clone();
+    assert_eq!(
+        spans,
+        [
+            "feed_span_with node=0 op=Put(1)",
+            "feed_span_with node=1 op=Get(2)",
+            "step state=nil<|[1,1]~",
+            "apply_head node=0 max_clock_skew=0",
+            "feed_span_with node=0 op=Stopped",
+            "step state=1<|[1,1]~",
+            "apply_head node=1 max_clock_skew=0",
+        ]
+    );
+    assert_eq!(
+        events,
+        [
+            "fed frontier=1 verdict=pass",
+            "applied node=0 op=Put(1) child=1<|[0,1]~0-Put(1)",
+            "settled common prefix settled=1",
+            "fed frontier=1 verdict=pass",
+            "pruned node=1 op=Get(2) reason=get value mismatch",
+            "violation detected node=0 op=Stopped",
+            "fed frontier=0 verdict=violated",
+        ]
+    );
+}
+
+#[test]
+fn timeline_render() {
+    let mut history = History::new();
+    history.push(0, OpSpan::put(8, 100, 105)).unwrap();
+    history.push(1, OpSpan::get(None, 101, 103)).unwrap();
+    history.push(1, OpSpan::fail(106, 110)).unwrap();
+    history.push(0, OpSpan::stopped(112)).unwrap();
+    assert_eq!(
+        history.timeline(13),
+        "    100       112\n\
+         n0 |[P8--]      x\n\
+         n1 | [Gnil]\n   \
+            |      [F--]\n"
+    );
+
+    // short ops rounding to the same columns get rows of their own
+    let mut history = History::new();
+    history.push(0, OpSpan::put(1, 1, 2)).unwrap();
+    history.push(0, OpSpan::get(Some(1), 3, 4)).unwrap();
+    history.push(1, OpSpan::put(2, 5, 1000)).unwrap();
+    assert_eq!(
+        history.timeline(20),
+        "    1               1000\n\
+         n0 |[P1]\n   \
+            |[G1]\n\
+         n1 |[P2----------------]\n"
+    );
+
+    // only ops concurrent with the unlinearized ones are drawn
+    let mut linearizer = Linearizer::new(2).with_keep_settled(true);
+    let node_ops: [(Node, OpSpan); 5] = [
+        (0, OpSpan::put(5, 1, 2)),
+        (1, OpSpan::get(Some(2), 5, 6)),
+        (0, OpSpan::put(1, 3, 10)),
+        (0, OpSpan::stopped(11)),
+        (1, OpSpan::stopped(12)),
+    ];
+    for (node, span) in node_ops {
+        linearizer.feed_span(node, span);
+    }
+    let timeline = linearizer.violation().unwrap().timeline(20);
+    assert!(timeline.contains("{G2"));
+    assert!(timeline.contains("[P1"));
+    assert!(!timeline.contains("P5"));
+}
+
+#[test]
+fn html_report() {
+    let mut history = History::new();
+    history.push(0, OpSpan::put(1, 1, 2)).unwrap();
+    history.push(1, OpSpan::get(Some(2), 3, 4)).unwrap();
+    history.push(1, OpSpan::cas(1, 3, 5, 6)).unwrap();
+    history.close();
+    let mut linearizer = Linearizer::new(2).with_keep_settled(true);
+    assert!(history.feed_into(&mut linearizer).is_some());
+
+    let mut html = vec![];
+    history
+        .write_html(linearizer.violation(), &mut html)
+        .unwrap();
+    let html = String::from_utf8(html).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(!html.contains("src=") && !html.contains("href="));
+    assert_eq!(html.matches("class=\"op linearized\"").count(), 1);
+    assert!(
+        html.contains("<g><title>0 Put(1)&lt;1&gt;-&lt;2&gt;</title><rect class=\"op linearized\"")
+    );
+    let unlinearized: Vec<&str> = html
+        .lines()
+        .filter(|line| line.contains("class=\"op unlinearized\""))
+        .map(|line| &line[line.find("<title>").unwrap() + 7..line.find("</title>").unwrap()])
+        .collect();
+    assert_eq!(
+        unlinearized,
+        [
+            "1 Get(2)&lt;3&gt;-&lt;4&gt;",
+            "1 Cas(1,3)&lt;5&gt;-&lt;6&gt;"
+        ]
+    );
+    assert!(html.contains("Cas(1,3)"));
+    assert!(html.contains("&lt;3&gt;-&lt;4&gt;"));
+}
+
+#[test]
+fn dot_export() {
+    let node_ops: [(Node, OpSpan); 5] = [
+        (0, OpSpan::put(1, 1, 10)),
+        (1, OpSpan::put(2, 2, 11)),
+        (0, OpSpan::get(Some(3), 12, 13)),
+        (1, OpSpan::stopped(14)),
+        (0, OpSpan::stopped(15)),
+    ];
+    let mut observer = DotObserver::new();
+    let mut last_two = DotObserver::new().with_last_feeds(2);
+    let mut linearizer = Linearizer::new(2);
+    for (node, span) in node_ops.clone() {
+        linearizer.feed_span_with(node, span, &mut observer);
+    }
+    let mut linearizer = Linearizer::new(2);
+    for (node, span) in node_ops {
+        linearizer.feed_span_with(node, span, &mut last_two);
+    }
+    assert_eq!(linearizer.verdict(), Verdict::Violated);
+
+    let mut dot = vec![];
+    observer.write_dot(&mut dot).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert!(dot.starts_with("digraph possibilities {"));
+    assert_eq!(dot.matches("subgraph cluster_").count(), 5);
+    assert!(dot.contains("[label=\"0 Put(1)\"]"));
+    assert!(dot.contains("[label=\"1 Put(2)\"]"));
+    assert!(dot.contains("Get(3) (get value mismatch)"));
+    assert!(dot.contains("color=red"));
+
+    let mut dot = vec![];
+    last_two.write_dot(&mut dot).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert_eq!(dot.matches("subgraph cluster_").count(), 2);
+    assert!(dot.contains("subgraph cluster_4 {"));
+}
+
+#[test]
+fn generated_histories() {
+    let mut generator = HistoryGenerator::new(3, 7).with_num_ops(200);
+    for _ in 0..20 {
+        let history = generator.generate();
+        assert_eq!(history.num_nodes(), 3);
+        let mut linearizer = Linearizer::new(3);
+        assert_eq!(history.feed_into(&mut linearizer), None);
+    }
+
+    // nodes without any op are still stopped
+    let history = HistoryGenerator::new(3, 7).with_num_ops(1).generate();
+    assert_eq!(history.num_nodes(), 3);
+    let mut linearizer = Linearizer::new(3);
+    assert_eq!(history.feed_into(&mut linearizer), None);
+    assert_eq!(linearizer.witness().unwrap().len(), 0);
+    assert_eq!(linearizer.num_settled(), 1);
+
+    let mut a = HistoryGenerator::new(2, 1);
+    let mut b = HistoryGenerator::new(2, 1);
+    assert_eq!(
+        format!("{:?}", a.generate().events()),
+        format!("{:?}", b.generate().events())
+    );
+
+    for faults in [
+        Faults {
+            stale_read: 0.5,
+            ..Faults::default()
+        },
+        Faults {
+            lost_write: 0.5,
+            ..Faults::default()
+        },
+    ] {
+        let mut generator = HistoryGenerator::new(3, 7)
+            .with_num_ops(12)
+            .with_faults(faults);
+        for _ in 0..20 {
+            let history = generator.generate();
+            let mut linearizer = Linearizer::new(3);
+            assert_eq!(
+                history.feed_into(&mut linearizer).is_none(),
+                history.is_linearizable_brute_force(),
+                "{:?}",
+                history.events()
+            );
+        }
+    }
+}
+
+#[test]
+fn brute_force_reference() {
+    let mut history = History::new();
+    history.push(0, OpSpan::put(1, 1, 4)).unwrap();
+    history.push(1, OpSpan::get(Some(1), 2, 3)).unwrap();
+    history.push(1, OpSpan::get(None, 5, 6)).unwrap();
+    assert!(!history.is_linearizable_brute_force());
+
+    for faults in [
+        Faults::default(),
+        Faults {
+            stale_read: 0.2,
+            lost_write: 0.2,
+        },
+    ] {
+        let mut generator = HistoryGenerator::new(3, 11)
+            .with_num_ops(12)
+            .with_fail_rate(0.1)
+            .with_faults(faults);
+        for _ in 0..50 {
+            let mut history = generator.generate();
+            history.close();
+            let mut linearizer = Linearizer::new(3);
+            assert_eq!(
+                history.feed_into(&mut linearizer).is_none(),
+                history.is_linearizable_brute_force(),
+                "{:?}",
+                history.events()
+            );
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod cross_check {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn online_matches_brute_force(mut history in arb_history(3, 10)) {
+            prop_assume!(history.num_nodes() > 0);
+            history.close();
+            let mut linearizer = Linearizer::new(history.num_nodes());
+            prop_assert_eq!(
+                history.feed_into(&mut linearizer).is_none(),
+                history.is_linearizable_brute_force()
+            );
+        }
+
+        #[test]
+        fn arb_events_are_valid(events in arb_events(4, 20)) {
+            let mut history = History::new();
+            for (node, span) in events {
+                prop_assert!(history.push(node, span).is_ok());
+            }
+        }
+    }
+}
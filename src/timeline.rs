@@ -0,0 +1,171 @@
+//! ASCII timeline rendering of histories and violations: one swimlane per
+//! node, with op spans drawn on a shared time axis.
+//!
+//! ```text
+//!     100                               112
+//! n0 |[P8--------]          x   >  [G8-]
+//! n1 |  [Gnil] [F---] {C8>9}
+//! ```
+//!
+//! Ops are drawn as `[label---]` (or `{label===}` when highlighted), where
+//! the label is `P<val>`, `G<val>`, `Gnil`, `C<old>><new>` or `F`; `x` and
+//! `>` mark a node being stopped and resumed. Ops are widened to fit their
+//! labels, and ops of a node that would overlap on screen are drawn on
+//! extra rows of its lane.
+
+use crate::{History, Node, OpInputs, OpResult, OpSpan, Timestamp, Violation};
+
+/// Short label of an op drawn on the timeline.
+fn label(span: &OpSpan) -> String {
+    match (&span.inputs, &span.result) {
+        (OpInputs::Put { val }, _) => format!("P{}", val),
+        (OpInputs::Get, OpResult::Get { val: Some(val) }) => format!("G{}", val),
+        (OpInputs::Get, _) => "Gnil".into(),
+        (OpInputs::Cas { old, new }, _) => format!("C{}>{}", old, new),
+        (OpInputs::Fail, _) => "F".into(),
+        (OpInputs::Stopped, _) => "x".into(),
+        (OpInputs::Resumed, _) => ">".into(),
+    }
+}
+
+/// Place `cells` starting at column `start` on the first row of a lane
+/// where they fit without overwriting anything, adding a row if none.
+fn place(rows: &mut Vec<Vec<char>>, start: usize, cells: &[char]) {
+    let end = start + cells.len();
+    let fits = |row: &Vec<char>| row.iter().skip(start).take(cells.len()).all(|c| *c == ' ');
+    let row = match rows.iter().position(fits) {
+        Some(i) => &mut rows[i],
+        None => {
+            rows.push(vec![]);
+            rows.last_mut().unwrap()
+        }
+    };
+    if row.len() < end {
+        row.resize(end, ' ');
+    }
+    row[start..end].copy_from_slice(cells);
+}
+
+/// Render `(node, span, highlighted)` events into per-node swimlanes about
+/// `width` columns wide. Ops are widened to fit their labels, and a lane
+/// gets extra rows where ops would otherwise overlap.
+fn render<'a>(
+    events: impl Iterator<Item = (Node, &'a OpSpan, bool)> + Clone,
+    width: usize,
+) -> String {
+    let width = width.max(2);
+    let num_nodes = events
+        .clone()
+        .map(|(node, _, _)| node + 1)
+        .max()
+        .unwrap_or(0);
+    let t0 = events
+        .clone()
+        .map(|(_, span, _)| span.ts_req)
+        .min()
+        .unwrap_or(0);
+    let t1 = events
+        .clone()
+        .map(|(_, span, _)| span.ts_ack)
+        .max()
+        .unwrap_or(0);
+    let col = |ts: Timestamp| {
+        if t1 == t0 {
+            0
+        } else {
+            ((ts - t0) as u128 * (width - 1) as u128 / (t1 - t0) as u128) as usize
+        }
+    };
+
+    let mut lanes: Vec<Vec<Vec<char>>> = vec![vec![vec![]]; num_nodes];
+    for (node, span, highlighted) in events {
+        let start = col(span.ts_req);
+        let label: Vec<char> = label(span).chars().collect();
+        if !span.is_normal() {
+            place(&mut lanes[node], start, &label);
+            continue;
+        }
+        let end = col(span.ts_ack).max(start + label.len() + 1);
+        let (open, fill, close) = if highlighted {
+            ('{', '=', '}')
+        } else {
+            ('[', '-', ']')
+        };
+        let mut cells = vec![fill; end - start + 1];
+        cells[0] = open;
+        cells[1..=label.len()].copy_from_slice(&label);
+        cells[end - start] = close;
+        place(&mut lanes[node], start, &cells);
+    }
+
+    let prefix = format!("n{}", num_nodes.saturating_sub(1)).len() + 1;
+    let (t0, t1) = (t0.to_string(), t1.to_string());
+    let mut out = format!(
+        "{:prefix$}{}{:>pad$}\n",
+        "",
+        t0,
+        t1,
+        prefix = prefix + 1,
+        pad = width.saturating_sub(t0.len()),
+    );
+    for (node, rows) in lanes.iter().enumerate() {
+        for (i, row) in rows.iter().enumerate() {
+            let name = if i == 0 {
+                format!("n{}", node)
+            } else {
+                String::new()
+            };
+            let row: String = row.iter().collect();
+            out.push_str(&format!(
+                "{:prefix$}|{}\n",
+                name,
+                row.trim_end(),
+                prefix = prefix
+            ));
+        }
+    }
+    out
+}
+
+impl History {
+    /// Render the history as an ASCII timeline about `width` columns wide.
+    pub fn timeline(&self, width: usize) -> String {
+        render(
+            self.events()
+                .iter()
+                .map(|(node, span)| (*node, span, false)),
+            width,
+        )
+    }
+}
+
+impl Violation {
+    /// Render the ops involved in the violation as an ASCII timeline about
+    /// `width` columns wide: the ones that could not be linearized,
+    /// highlighted, plus the linearized ones concurrent with any of them.
+    pub fn timeline(&self, width: usize) -> String {
+        let unlinearized: Vec<&OpSpan> = self
+            .unlinearized
+            .iter()
+            .map(|(_, span)| span)
+            .filter(|span| span.is_normal())
+            .collect();
+        let concurrent = |span: &OpSpan| {
+            unlinearized
+                .iter()
+                .any(|other| span.ts_req < other.ts_ack && other.ts_req < span.ts_ack)
+        };
+        render(
+            self.linearized
+                .iter()
+                .filter(|(_, span)| concurrent(span))
+                .map(|(node, span)| (*node, span, false))
+                .chain(
+                    self.unlinearized
+                        .iter()
+                        .map(|(node, span)| (*node, span, true)),
+                ),
+            width,
+        )
+    }
+}
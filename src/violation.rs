@@ -0,0 +1,45 @@
+//! Report of a detected violation, for explaining it to humans.
+
+use std::fmt;
+
+use crate::{Clock, Node, OpSpan, Timestamp};
+
+/// Report of a detected violation.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Violation<T: Clock = Timestamp> {
+    /// Node of the span whose feeding revealed the violation.
+    pub node: Node,
+
+    /// The span whose feeding revealed the violation.
+    pub span: OpSpan<T>,
+
+    /// Longest sequence of ops that could still be linearized, taken from
+    /// one of the possibilities that ran into a dead end (missing settled
+    /// ops that were discarded or taken).
+    pub linearized: Vec<(Node, OpSpan<T>)>,
+
+    /// Ops that could not be linearized after that sequence, i.e., the ones
+    /// left in that possibility's per-node queues.
+    pub unlinearized: Vec<(Node, OpSpan<T>)>,
+}
+
+impl<T: Clock> fmt::Display for Violation<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Violation revealed by node {} {:?}",
+            self.node, self.span
+        )?;
+        writeln!(f, "Longest linearizable prefix {{")?;
+        for (node, span) in &self.linearized {
+            writeln!(f, "  {} {:?}", node, span)?;
+        }
+        writeln!(f, "}}")?;
+        writeln!(f, "Cannot be linearized after it {{")?;
+        for (node, span) in &self.unlinearized {
+            writeln!(f, "  {} {:?}", node, span)?;
+        }
+        write!(f, "}}")
+    }
+}